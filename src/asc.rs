@@ -0,0 +1,57 @@
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+use std::path::Path;
+use super::{band_nodata, open_dataset};
+
+/// NoData value of band 1. For an Arc/Info ASCII Grid it is parsed from the
+/// `NODATA_value` header line, for other formats it is the one GDAL reports.
+/// Returns `None` if the file cannot be read or declares no NoData value.
+pub fn asc_nodata<P: AsRef<Path>>(filename: P) -> Option<f32>{
+    let filename = filename.as_ref();
+    let dataset = open_dataset(filename).ok()?;
+
+    if dataset.driver().short_name() == "AAIGrid" {
+        header_nodata(filename)
+    } else {
+        band_nodata(&dataset, 1).ok()?.map(|nodata| nodata as f32)
+    }
+}
+
+/// Reads the header lines, which start with a keyword, up to the first row of data.
+fn header_nodata(filename: &Path) -> Option<f32>{
+    let reader = BufReader::new(File::open(filename).ok()?);
+
+    for line in reader.lines() {
+        let line = line.ok()?;
+        let mut fields = line.split_whitespace();
+        let key = match fields.next() {
+            Some(key) if key.starts_with(|c: char| c.is_ascii_alphabetic()) => key,
+            _ => return None,
+        };
+        if key.eq_ignore_ascii_case("NODATA_value") {
+            return fields.next()?.parse().ok();
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod test_asc {
+    use super::*;
+
+    #[test]
+    fn test_asc_nodata(){
+        assert_eq!(asc_nodata("data/data1.asc"), Some(-32768.0));
+        assert_eq!(asc_nodata("data/data6.asc"), Some(-9999.0));
+    }
+
+    #[test]
+    fn test_asc_nodata_other_driver(){
+        assert_eq!(asc_nodata("data/stack.vrt"), Some(-32768.0));
+    }
+
+    #[test]
+    fn test_asc_nodata_missing(){
+        assert_eq!(asc_nodata("data/missing.asc"), None);
+    }
+}
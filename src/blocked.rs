@@ -0,0 +1,112 @@
+use std::collections::HashMap;
+use std::path::Path;
+use gdal::raster::{Buffer, Driver};
+use super::{band_nodata, check_shapes, ffi, open_dataset, path_str, Raster, RasterError, DEFAULT_GEOTRANSFORM};
+
+impl Raster<f32> {
+    /// Weighted sum of the maps, as `algebra`, written to the GeoTIFF `output`
+    /// in strips of `block_rows` rows, so that only one strip of each map is in
+    /// memory at a time. It checks first that all the maps have the same
+    /// dimensions. The output takes the NoData value and the georeferencing
+    /// of the first map in filename order. Zero maps write nothing, and zero
+    /// `block_rows` fail before reading anything.
+    pub fn algebra_blocked<P: AsRef<Path>>(maps: HashMap<String, f32>, output: P, block_rows: usize) -> Result<(), RasterError>{
+        if block_rows == 0 {
+            return Err(RasterError::BlockRows);
+        }
+
+        let mut maps: Vec<(String, f32)> = maps.into_iter().collect();
+        maps.sort_by(|a, b| a.0.cmp(&b.0));
+        check_shapes(maps.iter().map(|map| &map.0))?;
+
+        let datasets = maps.iter().map(|map| open_dataset(&map.0)).collect::<Result<Vec<_>, RasterError>>()?;
+        let first = match datasets.first() {
+            Some(dataset) => dataset,
+            // Zero items.
+            None => return Ok(()),
+        };
+        let (cols, rows) = first.size();
+
+        let driver = Driver::get("GTiff").map_err(|error| RasterError::Write(Box::new(error)))?;
+        let result = driver.create_with_band_type::<f32>(path_str(output.as_ref())?, cols as isize, rows as isize, 1)
+            .map_err(|error| RasterError::Write(Box::new(error)))?;
+        result.set_geo_transform(&first.geo_transform().unwrap_or(DEFAULT_GEOTRANSFORM))
+            .map_err(|error| RasterError::Write(Box::new(error)))?;
+        result.set_projection(&first.projection()).map_err(|error| RasterError::Write(Box::new(error)))?;
+        if let Some(nodata) = band_nodata(first, 1)? {
+            ffi::set_nodata(&result, 1, nodata).map_err(|error| RasterError::Write(Box::new(error)))?;
+        }
+
+        for start in (0..rows).step_by(block_rows) {
+            let size = (cols, block_rows.min(rows - start));
+            let offset = (0, start as isize);
+            let mut strip = vec![0.0; size.0 * size.1];
+
+            for (dataset, map) in datasets.iter().zip(&maps) {
+                let buffer = dataset.read_raster_as::<f32>(1, offset, size, size)?;
+                for (sum, value) in strip.iter_mut().zip(buffer.data) {
+                    *sum += map.1 * value;
+                }
+            }
+            result.write_raster(1, offset, size, &Buffer::new(size, strip))
+                .map_err(|error| RasterError::Write(Box::new(error)))?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test_blocked {
+    use super::*;
+    use std::env;
+    use std::fs;
+    use Mapping;
+
+    fn maps() -> HashMap<String, f32>{
+        let mut maps = HashMap::new();
+        maps.insert("data/data1.asc".to_string(), 0.4);
+        maps.insert("data/data2.asc".to_string(), 0.2);
+        maps.insert("data/data3.asc".to_string(), 0.2);
+        maps.insert("data/data4.asc".to_string(), 0.2);
+        maps
+    }
+
+    #[test]
+    fn test_algebra_blocked(){
+        for &block_rows in &[1, 2, 3, 10] {
+            let filename = env::temp_dir().join(format!("raster_mapping_blocked_{}.tif", block_rows));
+            Raster::<f32>::algebra_blocked(maps(), &filename, block_rows).unwrap();
+
+            let blocked = Raster::<f32>::new(&filename);
+            let combination = Raster::<f32>::algebra(maps());
+            assert_relative_eq!(blocked, combination, epsilon = 1e-5f32);
+            assert_eq!(blocked.nodata, Some(-32768.0));
+            assert_eq!(blocked.geo_transform(), combination.geo_transform());
+            fs::remove_file(&filename).unwrap();
+        }
+    }
+
+    #[test]
+    fn test_algebra_blocked_mismatch(){
+        let mut maps = maps();
+        maps.insert("data/data5.asc".to_string(), 0.1);
+        let filename = env::temp_dir().join("raster_mapping_blocked_mismatch.tif");
+
+        match Raster::<f32>::algebra_blocked(maps, &filename, 2) {
+            Err(RasterError::ShapeMismatch{ got, .. }) => assert_eq!(got, (2, 3)),
+            other => panic!("Expected a shape mismatch, got {:?}", other),
+        }
+        assert!(!filename.exists());
+    }
+
+    #[test]
+    fn test_algebra_blocked_zero_rows(){
+        let filename = env::temp_dir().join("raster_mapping_blocked_zero.tif");
+
+        match Raster::<f32>::algebra_blocked(maps(), &filename, 0) {
+            Err(RasterError::BlockRows) => {}
+            other => panic!("Expected a block size error, got {:?}", other),
+        }
+        assert!(!filename.exists());
+    }
+}
@@ -0,0 +1,140 @@
+use std::cmp::Ordering;
+use stats::percentile;
+use super::Raster;
+
+impl Raster<f32> {
+    /// Maps each pixel through the first rule `(low, high, new_value)` with
+    /// `low <= pixel < high`, or to `default` when no rule matches. Pixels
+    /// equal to the raster's NoData value are kept as they are.
+    pub fn reclassify(&self, rules: &[(f32, f32, f32)], default: f32) -> Raster<f32>{
+        let nodata = self.nodata_value();
+
+        self.with_data(self.data.mapv(|value| {
+            if Some(value) == nodata {
+                return value;
+            }
+            rules.iter()
+                .find(|&&(low, high, _)| low <= value && value < high)
+                .map_or(default, |&(_, _, new_value)| new_value)
+        }))
+    }
+
+    /// Assigns the classes 1 to `classes` so that each holds about the same
+    /// number of pixels not equal to `nodata`. The breaks are the interpolated
+    /// quantiles of the valid pixels, and a pixel equal to a break falls in
+    /// the lower class, so equal values always share a class. NoData pixels
+    /// get 0, which no class uses, as the output NoData value.
+    ///
+    /// Panics if `classes` is 0.
+    pub fn quantile_classify(&self, classes: usize, nodata: Option<f32>) -> Raster<i32>{
+        assert!(classes > 0, "The number of classes must be positive");
+
+        let mut sorted = self.valid_values(nodata);
+        sorted.sort_by(|a, b| a.partial_cmp(b).unwrap_or(Ordering::Equal));
+        let breaks: Vec<f32> = if sorted.is_empty() {
+            Vec::new()
+        } else {
+            (1..classes).map(|class| percentile(&sorted, 100.0 * class as f32 / classes as f32)).collect()
+        };
+
+        let mut raster = self.with_data(self.data.mapv(|value| match nodata {
+            Some(nodata) if value == nodata => 0,
+            _ => 1 + breaks.iter().filter(|&&limit| value > limit).count() as i32,
+        }));
+        raster.nodata = nodata.map(|_| 0.0);
+        raster
+    }
+
+    /// Maps every pixel exactly equal to `from` to `to`, such as to harmonize
+    /// NoData sentinels. The NoData value of the raster is not changed.
+    pub fn replace(&self, from: f32, to: f32) -> Raster<f32>{
+        self.with_data(self.data.mapv(|value| if value == from { to } else { value }))
+    }
+
+    /// Maps every NaN pixel to `fill`, which `replace` cannot match since NaN
+    /// is not equal to itself. The NoData value of the raster is not changed.
+    pub fn replace_nan(&self, fill: f32) -> Raster<f32>{
+        self.with_data(self.data.mapv(|value| if value.is_nan() { fill } else { value }))
+    }
+}
+
+#[cfg(test)]
+mod test_classify {
+    use super::*;
+
+    #[test]
+    fn test_reclassify(){
+        let raster = Raster{ nodata: Some(-9999.0), ..Raster::from_vec(vec![0.0, 0.1, 0.2, 0.3, 0.4, 0.5, 0.6, 0.7, 0.8, 0.9, 1.0, -9999.0], 3, 4).unwrap() };
+        let rules = [(0.0, 0.2, 1.0), (0.2, 0.6, 2.0), (0.6, 1.0, 3.0)];
+        let expected = vec![1.0, 1.0, 2.0, 2.0, 2.0, 2.0, 3.0, 3.0, 3.0, 3.0, 0.0, -9999.0];
+
+        assert_eq!(raster.reclassify(&rules, 0.0).data.into_raw_vec(), expected);
+    }
+
+    #[test]
+    fn test_reclassify_overlap(){
+        let raster = Raster::from_vec(vec![0.1, 0.3, 0.5, 0.7, 0.1, 0.3, 0.5, 0.7, 0.1, 0.3, 0.5, 0.7], 3, 4).unwrap();
+        // The first matching rule wins on [0.25, 0.5).
+        let rules = [(0.0, 0.5, 1.0), (0.25, 1.0, 2.0)];
+        let expected = vec![1.0, 1.0, 2.0, 2.0, 1.0, 1.0, 2.0, 2.0, 1.0, 1.0, 2.0, 2.0];
+
+        assert_eq!(raster.reclassify(&rules, 0.0).data.into_raw_vec(), expected);
+    }
+
+    #[test]
+    fn test_reclassify_gap(){
+        let raster = Raster::from_vec(vec![0.1, 0.3, 0.5, 0.7, 0.1, 0.3, 0.5, 0.7, 0.1, 0.3, 0.5, 0.7], 3, 4).unwrap();
+        let rules = [(0.0, 0.2, 1.0), (0.5, 1.0, 2.0)];
+        let expected = vec![1.0, -1.0, 2.0, 2.0, 1.0, -1.0, 2.0, 2.0, 1.0, -1.0, 2.0, 2.0];
+
+        assert_eq!(raster.reclassify(&rules, -1.0).data.into_raw_vec(), expected);
+    }
+
+    #[test]
+    fn test_quantile_classify(){
+        let raster = Raster::from_vec(vec![12.0, 3.0, 7.0, 1.0, 9.0, 5.0, 11.0, 2.0, 8.0, 4.0, 10.0, 6.0], 3, 4).unwrap();
+        let classes = raster.quantile_classify(3, None);
+
+        for class in 1..4 {
+            assert_eq!(classes.data.iter().filter(|&&value| value == class).count(), 4);
+        }
+        assert_eq!(classes.data.into_raw_vec(), vec![3, 1, 2, 1, 3, 2, 3, 1, 2, 1, 3, 2]);
+    }
+
+    #[test]
+    fn test_quantile_classify_nodata_and_ties(){
+        let raster = Raster{ nodata: Some(-9999.0), ..Raster::from_vec(vec![1.0, 1.0, 1.0, 1.0, 2.0, 2.0, 3.0, 4.0, -9999.0, -9999.0, 5.0, 6.0], 3, 4).unwrap() };
+        let classes = raster.quantile_classify(2, Some(-9999.0));
+
+        assert_eq!(classes.data.into_raw_vec(), vec![1, 1, 1, 1, 1, 1, 2, 2, 0, 0, 2, 2]);
+        assert_eq!(classes.nodata, Some(0.0));
+    }
+
+    #[test]
+    fn test_quantile_classify_small_sentinel(){
+        // Truncated, the sentinel would be the class 1.
+        let raster = Raster{ nodata: Some(1.5), ..Raster::from_vec(vec![1.0, 2.0, 3.0, 4.0, 5.0, 6.0, 7.0, 8.0, 9.0, 10.0, 11.0, 1.5], 3, 4).unwrap() };
+        let classes = raster.quantile_classify(2, Some(1.5));
+
+        assert_eq!(classes.data[[2, 3]], 0);
+        assert_eq!(classes.data.iter().filter(|&&class| class == 1).count(), 6);
+        assert_eq!(classes.nodata, Some(0.0));
+    }
+
+    #[test]
+    fn test_replace(){
+        let raster = Raster{ nodata: Some(-9999.0), ..Raster::from_vec(vec![0.1, -9999.0, 0.3, 0.4, 0.5, -9999.0, 0.7, 0.8, 0.9, 1.0, -9999.5, -9999.0], 3, 4).unwrap() };
+        let expected = vec![0.1, -32768.0, 0.3, 0.4, 0.5, -32768.0, 0.7, 0.8, 0.9, 1.0, -9999.5, -32768.0];
+
+        assert_eq!(raster.replace(-9999.0, -32768.0).data.into_raw_vec(), expected);
+    }
+
+    #[test]
+    fn test_replace_nan(){
+        let raster = Raster{ nodata: Some(-9999.0), ..Raster::from_vec(vec![0.1, f32::NAN, 0.3, f32::INFINITY, 0.5, -9999.0, f32::NAN, 0.8, 0.9, 1.0, 1.1, f32::NAN], 3, 4).unwrap() };
+        let expected = vec![0.1, -9999.0, 0.3, f32::INFINITY, 0.5, -9999.0, -9999.0, 0.8, 0.9, 1.0, 1.1, -9999.0];
+
+        assert_eq!(raster.replace_nan(-9999.0).data.into_raw_vec(), expected);
+        assert!(raster.replace(f32::NAN, -9999.0).data[[0, 1]].is_nan());
+    }
+}
@@ -0,0 +1,120 @@
+use std::cmp::Ordering;
+use super::Raster;
+
+/// Color ramp of `(position, [r, g, b])` stops, with positions in [0, 1] on
+/// the normalized pixel scale, interpolated linearly between the stops.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ColorMap {
+    stops: Vec<(f32, [u8; 3])>,
+}
+
+impl ColorMap {
+    /// Ramp through the given stops, in any order.
+    ///
+    /// Panics if there are no stops.
+    pub fn new(mut stops: Vec<(f32, [u8; 3])>) -> ColorMap{
+        assert!(!stops.is_empty(), "A color map needs at least one stop");
+        stops.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap_or(Ordering::Equal));
+        ColorMap{ stops }
+    }
+
+    /// Black to white.
+    pub fn grayscale() -> ColorMap{
+        ColorMap::new(vec![(0.0, [0, 0, 0]), (1.0, [255, 255, 255])])
+    }
+
+    /// The matplotlib viridis ramp, from dark purple to yellow, sampled at
+    /// five stops.
+    pub fn viridis() -> ColorMap{
+        ColorMap::new(vec![
+            (0.0, [68, 1, 84]),
+            (0.25, [59, 82, 139]),
+            (0.5, [33, 145, 140]),
+            (0.75, [94, 201, 98]),
+            (1.0, [253, 231, 37]),
+        ])
+    }
+
+    /// Color at `position`, the color of the first or last stop beyond them.
+    pub fn color(&self, position: f32) -> [u8; 3]{
+        let upper = match self.stops.iter().position(|stop| stop.0 >= position) {
+            Some(0) => return self.stops[0].1,
+            Some(upper) => upper,
+            None => return self.stops[self.stops.len() - 1].1,
+        };
+        let ((low, low_color), (high, high_color)) = (self.stops[upper - 1], self.stops[upper]);
+        let fraction = (position - low) / (high - low);
+
+        let mut color = [0; 3];
+        for (channel, (&from, &to)) in color.iter_mut().zip(low_color.iter().zip(high_color.iter())) {
+            *channel = (f32::from(from) + fraction * (f32::from(to) - f32::from(from))).round() as u8;
+        }
+        color
+    }
+}
+
+impl Raster<f32> {
+    /// RGBA8 pixels in row-major order, four bytes per pixel, for display.
+    /// The pixels not equal to `nodata` are normalized over their min and max,
+    /// as `normalize` does, then colored with `colormap` and made opaque.
+    /// NoData and NaN pixels are fully transparent black.
+    pub fn to_rgba(&self, colormap: &ColorMap, nodata: Option<f32>) -> Vec<u8>{
+        let normalized = self.normalize(nodata);
+
+        let mut rgba = Vec::with_capacity(self.data.len() * 4);
+        for (&value, &position) in self.data.iter().zip(normalized.data.iter()) {
+            if Some(value) == nodata || value.is_nan() {
+                rgba.extend_from_slice(&[0, 0, 0, 0]);
+            } else {
+                rgba.extend_from_slice(&colormap.color(position));
+                rgba.push(255);
+            }
+        }
+        rgba
+    }
+}
+
+#[cfg(test)]
+mod test_color {
+    use super::*;
+    use Mapping;
+
+    #[test]
+    fn test_color(){
+        let colormap = ColorMap::new(vec![(1.0, [255, 0, 0]), (0.0, [0, 0, 255]), (0.5, [0, 255, 0])]);
+
+        assert_eq!(colormap.color(0.0), [0, 0, 255]);
+        assert_eq!(colormap.color(0.25), [0, 128, 128]);
+        assert_eq!(colormap.color(0.5), [0, 255, 0]);
+        assert_eq!(colormap.color(1.0), [255, 0, 0]);
+        assert_eq!(colormap.color(-1.0), [0, 0, 255]);
+        assert_eq!(colormap.color(2.0), [255, 0, 0]);
+    }
+
+    #[test]
+    fn test_to_rgba(){
+        let raster = Raster::<f32>::new("data/data1.asc");
+        let rgba = raster.to_rgba(&ColorMap::viridis(), Some(-32768.0));
+        let (rows, cols) = raster.shape();
+
+        assert_eq!(rgba.len(), rows * cols * 4);
+        // The minimum at (0, 0) is the first stop and the maximum at (0, 3) the last.
+        assert_eq!(rgba[..4], [68, 1, 84, 255][..]);
+        assert_eq!(rgba[12..16], [253, 231, 37, 255][..]);
+        // (1, 3) is NoData.
+        assert_eq!(rgba[28..32], [0, 0, 0, 0][..]);
+    }
+
+    #[test]
+    fn test_to_rgba_grayscale(){
+        let raster = Raster::<f32>::from(array![[0.0, 5.0, 10.0, f32::NAN]]);
+
+        assert_eq!(raster.to_rgba(&ColorMap::grayscale(), None), vec![0, 0, 0, 255, 128, 128, 128, 255, 255, 255, 255, 255, 0, 0, 0, 0]);
+    }
+
+    #[test]
+    #[should_panic(expected = "A color map needs at least one stop")]
+    fn test_color_map_empty(){
+        ColorMap::new(Vec::new());
+    }
+}
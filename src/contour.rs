@@ -0,0 +1,199 @@
+use std::collections::BTreeMap;
+use super::Raster;
+
+/// Side between two neighboring pixel centers, as the `(row, col)` of the
+/// upper or left pixel, and whether the other one is below rather than to the
+/// right.
+type Side = (usize, usize, bool);
+
+/// Removes the segment between `from` and `to` from both ends.
+fn unlink(links: &mut BTreeMap<Side, Vec<Side>>, from: Side, to: Side){
+    for &(a, b) in &[(from, to), (to, from)] {
+        let remove = {
+            let targets = links.get_mut(&a).expect("every segment is linked from both ends");
+            let index = targets.iter().position(|&target| target == b).expect("every segment is linked from both ends");
+            targets.swap_remove(index);
+            targets.is_empty()
+        };
+        if remove {
+            links.remove(&a);
+        }
+    }
+}
+
+impl Raster<f32> {
+    /// Sides crossed by the contour at `level` with the segments joining them
+    /// in each cell between four pixel centers, traced by marching squares.
+    /// Cells with a NoData or NaN corner have no segments.
+    fn contour_segments(&self, level: f32) -> BTreeMap<Side, Vec<Side>>{
+        let nodata = self.nodata_value();
+        let (rows, cols) = self.data.dim();
+        let invalid = |value: f32| Some(value) == nodata || value.is_nan();
+
+        let mut links: BTreeMap<Side, Vec<Side>> = BTreeMap::new();
+        for row in 0..rows.saturating_sub(1) {
+            for col in 0..cols.saturating_sub(1) {
+                // Clockwise from the top left, and the sides from each corner
+                // to the next one.
+                let corners = [self.data[[row, col]], self.data[[row, col + 1]], self.data[[row + 1, col + 1]], self.data[[row + 1, col]]];
+                let sides = [(row, col, false), (row, col + 1, true), (row + 1, col, false), (row, col, true)];
+                if corners.iter().any(|&corner| invalid(corner)) {
+                    continue;
+                }
+
+                let above: Vec<bool> = corners.iter().map(|&corner| corner >= level).collect();
+                let crossed: Vec<Side> = (0..4).filter(|&i| above[i] != above[(i + 1) % 4]).map(|i| sides[i]).collect();
+                let mut link = |from: Side, to: Side| {
+                    links.entry(from).or_default().push(to);
+                    links.entry(to).or_default().push(from);
+                };
+                if crossed.len() == 2 {
+                    link(crossed[0], crossed[1]);
+                } else if crossed.len() == 4 {
+                    // A saddle: the segments cut off the corners on the other
+                    // side of the level from the mean of the four.
+                    let center = corners.iter().sum::<f32>() / 4.0 >= level;
+                    for i in (0..4).filter(|&i| above[i] != center) {
+                        link(sides[(i + 3) % 4], sides[i]);
+                    }
+                }
+            }
+        }
+        links
+    }
+
+    /// Position `(row, col)` of the crossing of `level` on `side`, interpolated
+    /// linearly between the pixel centers.
+    fn crossing(&self, side: Side, level: f32) -> (f64, f64){
+        let (row, col, down) = side;
+        let other = if down { (row + 1, col) } else { (row, col + 1) };
+        let (from, to) = (f64::from(self.data[[row, col]]), f64::from(self.data[other]));
+        let fraction = (f64::from(level) - from) / (to - from);
+
+        let (row, col) = (row as f64 + 0.5, col as f64 + 0.5);
+        if down { (row + fraction, col) } else { (row, col + fraction) }
+    }
+
+    /// Isolines at the levels `base + k * interval` between the minimum and
+    /// the maximum of the valid pixels, traced by marching squares over the
+    /// pixel centers. Each level comes with one polyline of world coordinates
+    /// `(x, y)` for each separate line, in increasing order of the levels, and
+    /// the levels without lines are left out. Closed lines end at their first
+    /// vertex. A pixel equal to the level counts as above it, and the saddles
+    /// are settled by the mean of the four pixels around them. NoData and NaN
+    /// pixels break the lines.
+    ///
+    /// Panics if `interval` is not positive.
+    pub fn contours(&self, interval: f32, base: f32) -> Vec<(f32, Vec<(f64, f64)>)>{
+        assert!(interval > 0.0, "The contour interval must be positive");
+
+        let nodata = self.nodata_value();
+        let valid: Vec<f32> = self.data.iter().cloned().filter(|&value| Some(value) != nodata && !value.is_nan()).collect();
+        if valid.is_empty() {
+            return Vec::new();
+        }
+        let min = valid.iter().cloned().fold(f32::INFINITY, f32::min);
+        let max = valid.iter().cloned().fold(f32::NEG_INFINITY, f32::max);
+
+        let gt = self.geotransform;
+        let world = |(row, col): (f64, f64)| (gt[0] + col * gt[1] + row * gt[2], gt[3] + col * gt[4] + row * gt[5]);
+
+        let mut contours = Vec::new();
+        let first = ((min - base) / interval).ceil() as i64;
+        let last = ((max - base) / interval).floor() as i64;
+        for k in first..=last {
+            let level = base + k as f32 * interval;
+            let mut links = self.contour_segments(level);
+
+            // Open lines start at a loose end, the closed ones anywhere.
+            while let Some(start) = links.iter().find(|link| link.1.len() == 1).or_else(|| links.iter().next()).map(|link| *link.0) {
+                let mut line = vec![start];
+                let mut current = start;
+                while let Some(&next) = links.get(&current).and_then(|targets| targets.first()) {
+                    unlink(&mut links, current, next);
+                    line.push(next);
+                    current = next;
+                }
+                contours.push((level, line.into_iter().map(|side| world(self.crossing(side, level))).collect()));
+            }
+        }
+        contours
+    }
+}
+
+#[cfg(test)]
+mod test_contour {
+    use super::*;
+    use ndarray::Array2;
+
+    #[test]
+    fn test_contours_plane(){
+        // Rising by one per column to the east.
+        let raster = Raster{
+            geotransform: [0.0, 10.0, 0.0, 50.0, 0.0, -10.0],
+            ..Raster::from(Array2::from_shape_fn((3, 5), |(_, col)| col as f32))
+        };
+        let contours = raster.contours(1.0, 0.5);
+
+        let levels: Vec<f32> = contours.iter().map(|contour| contour.0).collect();
+        assert_eq!(levels, vec![0.5, 1.5, 2.5, 3.5]);
+        for (k, contour) in contours.iter().enumerate() {
+            // Evenly spaced and north-south, through the pixel centers.
+            let x = 10.0 * (k + 1) as f64;
+            assert_eq!(contour.1, vec![(x, 45.0), (x, 35.0), (x, 25.0)]);
+        }
+    }
+
+    #[test]
+    fn test_contours_levels(){
+        let raster = Raster::from(Array2::from_shape_fn((2, 5), |(_, col)| 2.0 * col as f32));
+
+        let levels: Vec<f32> = raster.contours(3.0, 1.0).iter().map(|contour| contour.0).collect();
+        assert_eq!(levels, vec![1.0, 4.0, 7.0]);
+        // Nothing is below the minimum, so the level 0 has no line.
+        let levels: Vec<f32> = raster.contours(5.0, -100.0).iter().map(|contour| contour.0).collect();
+        assert_eq!(levels, vec![5.0]);
+    }
+
+    #[test]
+    fn test_contours_closed(){
+        let raster = Raster{
+            geotransform: [0.0, 10.0, 0.0, 50.0, 0.0, -10.0],
+            ..Raster::from(array![[0.0, 0.0, 0.0],
+                                  [0.0, 1.0, 0.0],
+                                  [0.0, 0.0, 0.0]])
+        };
+        let contours = raster.contours(1.0, 0.5);
+
+        assert_eq!(contours.len(), 1);
+        let line = &contours[0].1;
+        assert_eq!(line.len(), 5);
+        assert_eq!(line.first(), line.last());
+        // Half way between the peak at (15, 35) and its neighbors.
+        for &(x, y) in line {
+            assert_relative_eq!((x - 15.0).abs() + (y - 35.0).abs(), 5.0);
+        }
+    }
+
+    #[test]
+    fn test_contours_nodata(){
+        let mut data = Array2::from_shape_fn((5, 3), |(_, col)| col as f32);
+        data[[2, 1]] = -9999.0;
+        let contours = Raster{ nodata: Some(-9999.0), geotransform: [0.0, 10.0, 0.0, 50.0, 0.0, -10.0], ..Raster::from(data) }.contours(1.0, 0.5);
+
+        // The line at 0.5 is cut where the cells touch the NoData pixel.
+        assert_eq!(contours, vec![
+            (0.5, vec![(10.0, 45.0), (10.0, 35.0)]),
+            (0.5, vec![(10.0, 15.0), (10.0, 5.0)]),
+            (1.5, vec![(20.0, 45.0), (20.0, 35.0)]),
+            (1.5, vec![(20.0, 15.0), (20.0, 5.0)]),
+        ]);
+        assert!(Raster{ nodata: Some(-9999.0), ..Raster::from(Array2::from_elem((2, 2), -9999.0)) }.contours(1.0, 0.0).is_empty());
+    }
+
+    #[test]
+    #[should_panic(expected = "The contour interval must be positive")]
+    fn test_contours_interval(){
+        Raster::from(Array2::zeros((2, 2))).contours(0.0, 0.0);
+    }
+}
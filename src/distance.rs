@@ -0,0 +1,248 @@
+use std::cmp::Ordering;
+use std::collections::BinaryHeap;
+use ndarray::{Array1, Array2};
+use super::{Raster, RasterError};
+
+const NEIGHBORS: [(isize, isize); 8] = [(-1, -1), (-1, 0), (-1, 1), (0, -1), (0, 1), (1, -1), (1, 0), (1, 1)];
+
+/// Pending cell of the Dijkstra traversal, ordered so that `BinaryHeap` pops
+/// the lowest cost first.
+#[derive(PartialEq)]
+struct Step {
+    cost: f32,
+    index: (usize, usize),
+}
+
+impl Eq for Step {}
+
+impl Ord for Step {
+    fn cmp(&self, other: &Step) -> Ordering {
+        other.cost.partial_cmp(&self.cost).unwrap_or(Ordering::Equal)
+    }
+}
+
+impl PartialOrd for Step {
+    fn partial_cmp(&self, other: &Step) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// Squared distance from each position to the nearest finite entry of `f`,
+/// plus the value at that entry, with `spacing` between the positions: the
+/// lower envelope of the parabolas of Felzenszwalb and Huttenlocher.
+fn squared_distance_1d(f: &[f64], spacing: f64) -> Vec<f64>{
+    let position = |index: usize| index as f64 * spacing;
+    let intersection = |p: usize, q: usize| {
+        ((f[q] + position(q).powi(2)) - (f[p] + position(p).powi(2))) / (2.0 * (position(q) - position(p)))
+    };
+
+    // The parabolas of the envelope, with where each one starts to be lowest.
+    let mut sites: Vec<usize> = Vec::with_capacity(f.len());
+    let mut starts: Vec<f64> = Vec::with_capacity(f.len());
+    for q in (0..f.len()).filter(|&q| f[q].is_finite()) {
+        let mut start = f64::NEG_INFINITY;
+        while let Some(&p) = sites.last() {
+            let crossing = intersection(p, q);
+            if crossing <= starts[starts.len() - 1] {
+                sites.pop();
+                starts.pop();
+            } else {
+                start = crossing;
+                break;
+            }
+        }
+        sites.push(q);
+        starts.push(start);
+    }
+
+    if sites.is_empty() {
+        return vec![f64::INFINITY; f.len()];
+    }
+    let mut k = 0;
+    (0..f.len()).map(|index| {
+        while k + 1 < sites.len() && starts[k + 1] < position(index) {
+            k += 1;
+        }
+        (position(index) - position(sites[k])).powi(2) + f[sites[k]]
+    }).collect()
+}
+
+impl Raster<u8> {
+    /// Euclidean distance from each pixel center to the nearest nonzero pixel
+    /// center, in the units of the geotransform, from the pixel width and
+    /// height, which may differ. Nonzero pixels get 0 and, without any, every
+    /// pixel is infinite. The distance transform is exact, a pass along the
+    /// rows and then one along the columns. Pixels equal to the NoData value
+    /// of the mask are not sources, but distances are measured across them,
+    /// and they come out as `nodata`, or NaN without one, which is then the
+    /// NoData value.
+    pub fn distance(&self, nodata: Option<f32>) -> Raster<f32>{
+        let (rows, cols) = self.data.dim();
+        let (width, height) = (self.geotransform[1].hypot(self.geotransform[4]), self.geotransform[2].hypot(self.geotransform[5]));
+        let masked = |value: u8| Some(f64::from(value)) == self.nodata;
+
+        let mut squared = self.data.mapv(|value| if value != 0 && !masked(value) { 0.0 } else { f64::INFINITY });
+        for mut row in squared.genrows_mut() {
+            let distances = squared_distance_1d(&row.to_vec(), width);
+            row.assign(&Array1::from_vec(distances));
+        }
+        for mut col in squared.gencolumns_mut() {
+            let distances = squared_distance_1d(&col.to_vec(), height);
+            col.assign(&Array1::from_vec(distances));
+        }
+
+        let fill = nodata.unwrap_or(f32::NAN);
+        let data = Array2::from_shape_fn((rows, cols), |index| {
+            if masked(self.data[index]) { fill } else { squared[index].sqrt() as f32 }
+        });
+        let mut raster = self.with_data(data);
+        raster.nodata = Some(f64::from(fill));
+        raster
+    }
+}
+
+impl Raster<f32> {
+    /// Minimum accumulated cost from the nonzero `sources` to every pixel over
+    /// the 8 neighbors, where each step adds the cost of the pixel stepped
+    /// into, diagonal steps included. Pixels equal to the NoData value of this
+    /// raster or of `cost` are barriers that come out as this raster's NoData
+    /// value, or NaN without one. Pixels no source reaches are infinite.
+    ///
+    /// Panics if the shapes differ, see `try_cost_distance`.
+    pub fn cost_distance(&self, sources: &Raster<u8>, cost: &Raster<f32>) -> Raster<f32>{
+        self.try_cost_distance(sources, cost).unwrap_or_else(|error| panic!("Cannot compute the cost distance: {}", error))
+    }
+
+    /// Accumulated cost distance, failing if the shapes differ.
+    pub fn try_cost_distance(&self, sources: &Raster<u8>, cost: &Raster<f32>) -> Result<Raster<f32>, RasterError>{
+        self.check_shape(sources)?;
+        self.check_shape(cost)?;
+
+        let (rows, cols) = self.data.dim();
+        let (nodata, cost_nodata) = (self.nodata_value(), cost.nodata_value());
+        let barrier = |index: (usize, usize)| Some(self.data[index]) == nodata || Some(cost.data[index]) == cost_nodata;
+
+        let mut distance = Array2::from_elem((rows, cols), f32::INFINITY);
+        let mut heap = BinaryHeap::new();
+        for (index, &source) in sources.data.indexed_iter() {
+            if source != 0 && !barrier(index) {
+                distance[index] = 0.0;
+                heap.push(Step{ cost: 0.0, index });
+            }
+        }
+
+        while let Some(Step{ cost: current, index: (row, col) }) = heap.pop() {
+            if current > distance[[row, col]] {
+                continue;
+            }
+            for &(dr, dc) in NEIGHBORS.iter() {
+                let (r, c) = (row as isize + dr, col as isize + dc);
+                if r < 0 || c < 0 || r as usize >= rows || c as usize >= cols {
+                    continue;
+                }
+                let next = (r as usize, c as usize);
+                if barrier(next) {
+                    continue;
+                }
+                let total = current + cost.data[next];
+                if total < distance[next] {
+                    distance[next] = total;
+                    heap.push(Step{ cost: total, index: next });
+                }
+            }
+        }
+
+        let fill = nodata.unwrap_or(f32::NAN);
+        for (index, item) in distance.indexed_iter_mut() {
+            if barrier(index) {
+                *item = fill;
+            }
+        }
+
+        let mut raster = self.with_data(distance);
+        raster.nodata = Some(f64::from(fill));
+        Ok(raster)
+    }
+}
+
+#[cfg(test)]
+mod test_distance {
+    use super::*;
+
+    #[test]
+    fn test_cost_distance(){
+        let grid = Raster::<f32>::from(Array2::zeros((4, 5)));
+        let mut sources = Raster::<u8>::from(Array2::zeros((4, 5)));
+        sources.data[[1, 1]] = 1;
+        let cost = Raster::<f32>::from(Array2::from_elem((4, 5), 2.0));
+
+        let distance = grid.cost_distance(&sources, &cost);
+        // Uniform costs give twice the chessboard distance to the source.
+        for ((row, col), &value) in distance.data.indexed_iter() {
+            let steps = (row as isize - 1).abs().max((col as isize - 1).abs());
+            assert_eq!(value, 2.0 * steps as f32);
+        }
+    }
+
+    #[test]
+    fn test_cost_distance_barriers(){
+        let mut grid = Raster::<f32>::from(array![[0.0, -1.0, 0.0],
+                                                  [0.0, -1.0, 0.0],
+                                                  [0.0, -1.0, 0.0]]);
+        grid.nodata = Some(-1.0);
+        let sources = Raster::<u8>::from(array![[1, 0, 0], [0, 0, 0], [0, 0, 0]]);
+        let cost = Raster::<f32>::from(array![[1.0, 1.0, 1.0], [5.0, 1.0, 1.0], [1.0, 1.0, 1.0]]);
+
+        let distance = grid.cost_distance(&sources, &cost);
+        assert_eq!(distance.data.column(0).to_vec(), vec![0.0, 5.0, 6.0]);
+        assert_eq!(distance.data.column(1).to_vec(), vec![-1.0; 3]);
+        assert_eq!(distance.data.column(2).to_vec(), vec![f32::INFINITY; 3]);
+    }
+
+    #[test]
+    fn test_distance_single(){
+        let mut data = Array2::zeros((5, 7));
+        data[[2, 3]] = 1;
+        let mask = Raster{ geotransform: [0.0, 10.0, 0.0, 50.0, 0.0, -20.0], ..Raster::<u8>::from(data) };
+        let distance = mask.distance(Some(-1.0));
+
+        // Pixels are 10 wide and 20 high.
+        for ((row, col), &value) in distance.data.indexed_iter() {
+            let (dy, dx) = (20.0 * (row as f32 - 2.0), 10.0 * (col as f32 - 3.0));
+            assert_relative_eq!(value, dx.hypot(dy), epsilon = 1e-4);
+        }
+        // Growing radially from the source.
+        for offset in 1..3 {
+            assert!(distance.data[[2, 3 + offset]] < distance.data[[2, 4 + offset]]);
+            assert!(distance.data[[2 - offset, 3 - offset]] < distance.data[[2 - offset, 2 - offset]]);
+            assert_eq!(distance.data[[2, 3 - offset]], distance.data[[2, 3 + offset]]);
+        }
+        assert_eq!(distance.data[[2, 3]], 0.0);
+    }
+
+    #[test]
+    fn test_distance_nearest(){
+        let mask = Raster{
+            nodata: Some(255.0),
+            geotransform: [0.0, 10.0, 0.0, 50.0, 0.0, -20.0],
+            ..Raster::<u8>::from(array![[1, 0, 0, 0, 0, 255, 0, 1]])
+        };
+        let distance = mask.distance(None);
+
+        assert_eq!(distance.data.slice(s![0, ..5]), array![0.0, 10.0, 20.0, 30.0, 30.0]);
+        assert!(distance.data[[0, 5]].is_nan());
+        assert_eq!(distance.data.slice(s![0, 6..]), array![10.0, 0.0]);
+        assert!(distance.nodata.unwrap().is_nan());
+
+        let empty = Raster::<u8>::from(Array2::zeros((2, 3))).distance(Some(-1.0));
+        assert_eq!(empty.data, Array2::from_elem((2, 3), f32::INFINITY));
+    }
+
+    #[test]
+    fn test_cost_distance_mismatch(){
+        let grid = Raster::<f32>::from(Array2::zeros((2, 2)));
+        let sources = Raster::<u8>::from(Array2::zeros((2, 3)));
+
+        assert!(grid.try_cost_distance(&sources, &grid).is_err());
+    }
+}
@@ -0,0 +1,119 @@
+use std::error;
+use std::fmt;
+use std::io;
+use std::path::PathBuf;
+use gdal::errors::Error as GdalError;
+
+#[derive(Debug)]
+pub enum RasterError {
+    /// The file could not be opened as a GDAL dataset.
+    Open(PathBuf, Box<GdalError>),
+    /// The requested 1-based band is not in the dataset.
+    Band { band: isize, count: isize },
+    /// The raster band could not be read.
+    Read(Box<GdalError>),
+    /// The raster could not be written with GDAL.
+    Write(Box<GdalError>),
+    /// The buffer read from GDAL does not fit the dataset dimensions.
+    Shape { expected: (usize, usize), got: usize },
+    /// A file does not have the dimensions of the first file combined with it.
+    ShapeMismatch { file: String, expected: (usize, usize), got: (usize, usize) },
+    /// Two rasters combined pixel by pixel have different dimensions.
+    DimensionMismatch { expected: (usize, usize), got: (usize, usize) },
+    /// A tile of a mosaic is not on the north-up pixel grid of the first one.
+    Grid { file: String },
+    /// A path to write to is not valid UTF-8, which GDAL needs.
+    Path(PathBuf),
+    /// A configuration file could not be read.
+    Io(PathBuf, io::Error),
+    /// A line of a configuration file, counting from 1, is malformed.
+    Config { file: PathBuf, line: usize, message: String },
+    /// The number of weights is not the number of bands of a stack.
+    WeightCount { bands: usize, weights: usize },
+    /// A weight of a validated weighted overlay is negative, infinite or NaN.
+    InvalidWeight { file: String, weight: f64 },
+    /// The weights of a validated weighted overlay do not sum to 1.
+    WeightSum { sum: f64, tolerance: f64 },
+    /// A map algebra expression is malformed at the character `position`.
+    Expression { position: usize, message: String },
+    /// A blocked algebra was asked for blocks of zero rows.
+    BlockRows,
+    /// The integer algebra overflowed `i16` at the pixel `(row, col)`.
+    Overflow { row: usize, col: usize },
+    /// The raster could not be reprojected with GDAL.
+    Warp(Box<GdalError>),
+    /// A window, as GDAL `(x, y)` offset and size, is not inside the raster.
+    Window { offset: (isize, isize), size: (usize, usize), raster: (usize, usize) },
+}
+
+impl fmt::Display for RasterError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            RasterError::Open(ref path, ref error) => {
+                write!(f, "could not open raster {}: {}", path.display(), error)
+            }
+            RasterError::Band { band, count } => {
+                write!(f, "band {} is out of range, the dataset has {} bands", band, count)
+            }
+            RasterError::Read(ref error) => {
+                write!(f, "could not read raster band: {}", error)
+            }
+            RasterError::Write(ref error) => {
+                write!(f, "could not write raster: {}", error)
+            }
+            RasterError::Shape { expected, got } => {
+                write!(f, "buffer of {} pixels does not fit the shape {:?}", got, expected)
+            }
+            RasterError::ShapeMismatch { ref file, expected, got } => {
+                write!(f, "{} has shape {:?}, expected {:?}", file, got, expected)
+            }
+            RasterError::DimensionMismatch { expected, got } => {
+                write!(f, "expected a raster of shape {:?}, got {:?}", expected, got)
+            }
+            RasterError::Grid { ref file } => {
+                write!(f, "{} is not on the pixel grid of the first raster", file)
+            }
+            RasterError::Path(ref path) => {
+                write!(f, "{} is not a valid UTF-8 path", path.display())
+            }
+            RasterError::Io(ref path, ref error) => {
+                write!(f, "could not read {}: {}", path.display(), error)
+            }
+            RasterError::Config { ref file, line, ref message } => {
+                write!(f, "{} line {}: {}", file.display(), line, message)
+            }
+            RasterError::WeightCount { bands, weights } => {
+                write!(f, "got {} weights for a stack of {} bands", weights, bands)
+            }
+            RasterError::InvalidWeight { ref file, weight } => {
+                write!(f, "{} has the weight {}, which is negative or not finite", file, weight)
+            }
+            RasterError::WeightSum { sum, tolerance } => {
+                write!(f, "the weights sum to {}, not 1 within {}", sum, tolerance)
+            }
+            RasterError::Expression { position, ref message } => {
+                write!(f, "invalid expression at character {}: {}", position, message)
+            }
+            RasterError::BlockRows => {
+                write!(f, "the number of rows per block must be positive")
+            }
+            RasterError::Overflow { row, col } => {
+                write!(f, "integer overflow at pixel ({}, {})", row, col)
+            }
+            RasterError::Warp(ref error) => {
+                write!(f, "could not reproject raster: {}", error)
+            }
+            RasterError::Window { offset, size, raster } => {
+                write!(f, "window at {:?} of size {:?} is outside the raster of size {:?}", offset, size, raster)
+            }
+        }
+    }
+}
+
+impl error::Error for RasterError {}
+
+impl From<GdalError> for RasterError {
+    fn from(error: GdalError) -> RasterError {
+        RasterError::Read(Box::new(error))
+    }
+}
@@ -0,0 +1,303 @@
+use std::collections::HashMap;
+use ndarray::Array2;
+use super::{Mapping, Raster, RasterError};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Op {
+    Add,
+    Sub,
+    Mul,
+    Div
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Number(f32),
+    Name(String),
+    Op(Op),
+    Open,
+    Close
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Expr {
+    Number(f32),
+    /// A variable and the character position of its token.
+    Var(String, usize),
+    Neg(Box<Expr>),
+    Binary(Op, Box<Expr>, Box<Expr>)
+}
+
+fn invalid(position: usize, message: String) -> RasterError{
+    RasterError::Expression{ position, message }
+}
+
+/// Splits the expression into tokens with their character position.
+fn tokenize(expr: &str) -> Result<Vec<(usize, Token)>, RasterError>{
+    let chars: Vec<char> = expr.chars().collect();
+    let mut tokens = Vec::new();
+    let mut index = 0;
+
+    while index < chars.len() {
+        let start = index;
+        let c = chars[index];
+        let token = match c {
+            '+' => Token::Op(Op::Add),
+            '-' => Token::Op(Op::Sub),
+            '*' => Token::Op(Op::Mul),
+            '/' => Token::Op(Op::Div),
+            '(' => Token::Open,
+            ')' => Token::Close,
+            _ if c.is_whitespace() => {
+                index += 1;
+                continue;
+            }
+            _ if c.is_ascii_digit() || c == '.' => {
+                while index < chars.len() && (chars[index].is_ascii_digit() || chars[index] == '.') {
+                    index += 1;
+                }
+                let literal: String = chars[start..index].iter().collect();
+                let value = literal.parse().map_err(|_| invalid(start, format!("invalid number {:?}", literal)))?;
+                tokens.push((start, Token::Number(value)));
+                continue;
+            }
+            _ if c.is_alphabetic() || c == '_' => {
+                while index < chars.len() && (chars[index].is_alphanumeric() || chars[index] == '_') {
+                    index += 1;
+                }
+                tokens.push((start, Token::Name(chars[start..index].iter().collect())));
+                continue;
+            }
+            _ => return Err(invalid(start, format!("unexpected character {:?}", c))),
+        };
+        tokens.push((start, token));
+        index += 1;
+    }
+    Ok(tokens)
+}
+
+/// Recursive descent parser where `*` and `/` bind tighter than `+` and `-`,
+/// all of them left associative.
+struct Parser {
+    tokens: Vec<(usize, Token)>,
+    next: usize,
+    end: usize
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token>{
+        self.tokens.get(self.next).map(|(_, token)| token)
+    }
+
+    fn position(&self) -> usize{
+        self.tokens.get(self.next).map_or(self.end, |&(position, _)| position)
+    }
+
+    fn binary(&mut self, ops: &[Op], operand: fn(&mut Parser) -> Result<Expr, RasterError>) -> Result<Expr, RasterError>{
+        let mut left = operand(self)?;
+        while let Some(&Token::Op(op)) = self.peek() {
+            if !ops.contains(&op) {
+                break;
+            }
+            self.next += 1;
+            left = Expr::Binary(op, Box::new(left), Box::new(operand(self)?));
+        }
+        Ok(left)
+    }
+
+    fn expression(&mut self) -> Result<Expr, RasterError>{
+        self.binary(&[Op::Add, Op::Sub], Parser::term)
+    }
+
+    fn term(&mut self) -> Result<Expr, RasterError>{
+        self.binary(&[Op::Mul, Op::Div], Parser::factor)
+    }
+
+    fn factor(&mut self) -> Result<Expr, RasterError>{
+        let position = self.position();
+        let token = self.peek().cloned();
+        self.next += 1;
+
+        match token {
+            Some(Token::Number(value)) => Ok(Expr::Number(value)),
+            Some(Token::Name(name)) => Ok(Expr::Var(name, position)),
+            Some(Token::Op(Op::Sub)) => Ok(Expr::Neg(Box::new(self.factor()?))),
+            Some(Token::Open) => {
+                let inner = self.expression()?;
+                if self.peek() != Some(&Token::Close) {
+                    return Err(invalid(self.position(), "expected ')'".to_string()));
+                }
+                self.next += 1;
+                Ok(inner)
+            }
+            Some(token) => Err(invalid(position, format!("unexpected {:?}", token))),
+            None => Err(invalid(position, "unexpected end of expression".to_string())),
+        }
+    }
+}
+
+fn parse(expr: &str) -> Result<Expr, RasterError>{
+    let mut parser = Parser{ tokens: tokenize(expr)?, next: 0, end: expr.chars().count() };
+    let ast = parser.expression()?;
+    if parser.next < parser.tokens.len() {
+        return Err(invalid(parser.position(), "expected an operator".to_string()));
+    }
+    Ok(ast)
+}
+
+/// Variable names in order of first appearance, with the position of that
+/// appearance.
+fn variables(ast: &Expr, names: &mut Vec<(String, usize)>){
+    match *ast {
+        Expr::Number(_) => (),
+        Expr::Var(ref name, position) => if names.iter().all(|known| &known.0 != name) {
+            names.push((name.clone(), position));
+        },
+        Expr::Neg(ref inner) => variables(inner, names),
+        Expr::Binary(_, ref left, ref right) => {
+            variables(left, names);
+            variables(right, names);
+        }
+    }
+}
+
+/// Value of the expression at one pixel, None where a variable is NoData or
+/// a division is by zero.
+fn evaluate(ast: &Expr, pixel: &dyn Fn(&str) -> Option<f32>) -> Option<f32>{
+    match *ast {
+        Expr::Number(value) => Some(value),
+        Expr::Var(ref name, _) => pixel(name),
+        Expr::Neg(ref inner) => evaluate(inner, pixel).map(|value| -value),
+        Expr::Binary(op, ref left, ref right) => {
+            let (left, right) = (evaluate(left, pixel)?, evaluate(right, pixel)?);
+            match op {
+                Op::Add => Some(left + right),
+                Op::Sub => Some(left - right),
+                Op::Mul => Some(left * right),
+                Op::Div if right == 0.0 => None,
+                Op::Div => Some(left / right),
+            }
+        }
+    }
+}
+
+impl Raster<f32> {
+    /// Evaluates an infix expression of `+ - * /`, parentheses, numbers and
+    /// variables pixel by pixel, where `vars` maps each variable to a raster
+    /// file, for instance `(a + b) / 2 - c * 0.3`. The files must have the
+    /// same dimensions. A pixel is NoData where any variable it uses is its
+    /// file's NoData value or a division is by zero. The result takes the
+    /// georeferencing and the NoData value, or NaN without one, of the first
+    /// variable in the expression.
+    pub fn eval(expr: &str, vars: HashMap<String, String>) -> Result<Raster<f32>, RasterError>{
+        let ast = parse(expr)?;
+        let mut names = Vec::new();
+        variables(&ast, &mut names);
+
+        let mut rasters: HashMap<&str, Raster<f32>> = HashMap::new();
+        for &(ref name, position) in &names {
+            let filename = vars.get(name)
+                .ok_or_else(|| invalid(position, format!("unknown variable {}", name)))?;
+            let raster = Raster::<f32>::try_new(filename)?;
+            if let Some(first) = names.first().and_then(|first| rasters.get(first.0.as_str())) {
+                first.check_shape(&raster)?;
+            }
+            rasters.insert(name, raster);
+        }
+
+        let first = match names.first() {
+            Some(name) => &rasters[name.0.as_str()],
+            None => return Err(invalid(0, "the expression has no raster variable".to_string())),
+        };
+        let fill = first.nodata_value().unwrap_or(f32::NAN);
+
+        let data = Array2::from_shape_fn(first.data.dim(), |index| {
+            let pixel = |name: &str| {
+                let raster = &rasters[name];
+                let value = raster.data[index];
+                if Some(value) == raster.nodata_value() { None } else { Some(value) }
+            };
+            evaluate(&ast, &pixel).unwrap_or(fill)
+        });
+
+        let mut raster = first.with_data(data);
+        raster.nodata = Some(f64::from(fill));
+        Ok(raster)
+    }
+}
+
+#[cfg(test)]
+mod test_expr {
+    use super::*;
+
+    fn vars(names: &[(&str, &str)]) -> HashMap<String, String> {
+        names.iter().map(|&(name, filename)| (name.to_string(), filename.to_string())).collect()
+    }
+
+    #[test]
+    fn test_parse_precedence(){
+        let product = Expr::Binary(Op::Mul, Box::new(Expr::Var("b".to_string(), 4)), Box::new(Expr::Number(2.0)));
+        let expected = Expr::Binary(Op::Sub, Box::new(Expr::Binary(Op::Add, Box::new(Expr::Var("a".to_string(), 0)), Box::new(product))),
+                                    Box::new(Expr::Number(1.0)));
+
+        assert_eq!(parse("a + b * 2 - 1").unwrap(), expected);
+        assert_eq!(parse("-(a)").unwrap(), Expr::Neg(Box::new(Expr::Var("a".to_string(), 2))));
+    }
+
+    #[test]
+    fn test_eval_precedence(){
+        let a = Raster::<f32>::new("data/data1.asc");
+        let b = Raster::<f32>::new("data/data2.asc");
+        let c = Raster::<f32>::new("data/data3.asc");
+        let result = Raster::<f32>::eval("(a + b) / 2 - c * 0.3",
+                                         vars(&[("a", "data/data1.asc"), ("b", "data/data2.asc"), ("c", "data/data3.asc")])).unwrap();
+
+        // The three files have NoData at the same pixels.
+        for (index, &value) in result.data.indexed_iter() {
+            if a.data[index] == -32768.0 {
+                assert_eq!(value, -32768.0);
+            } else {
+                assert_relative_eq!(value, (a.data[index] + b.data[index]) / 2.0 - c.data[index] * 0.3, epsilon = 1e-6);
+            }
+        }
+        assert_eq!(result.geo_transform(), a.geo_transform());
+    }
+
+    #[test]
+    fn test_eval_nodata(){
+        let result = Raster::<f32>::eval("a * 2 + 1", vars(&[("a", "data/data1.asc")])).unwrap();
+
+        assert_eq!(result.nodata, Some(-32768.0));
+        assert_eq!(result[(2, 3)], -32768.0);
+        assert_relative_eq!(result[(0, 0)], 0.388889 * 2.0 + 1.0, epsilon = 1e-5);
+    }
+
+    #[test]
+    fn test_eval_division_by_zero(){
+        let result = Raster::<f32>::eval("a / (a - a)", vars(&[("a", "data/data1.asc")])).unwrap();
+
+        assert!(result.data.iter().all(|&value| value == -32768.0));
+    }
+
+    #[test]
+    fn test_eval_errors(){
+        let files = vars(&[("a", "data/data1.asc")]);
+
+        match Raster::<f32>::eval("a + b", files.clone()) {
+            Err(RasterError::Expression{ position, .. }) => assert_eq!(position, 4),
+            other => panic!("Expected an expression error, got {:?}", other),
+        }
+        // The position of the token, not of the first match in the text.
+        match Raster::<f32>::eval("a * ab + b", vars(&[("a", "data/data1.asc"), ("ab", "data/data2.asc")])) {
+            Err(RasterError::Expression{ position, .. }) => assert_eq!(position, 9),
+            other => panic!("Expected an expression error, got {:?}", other),
+        }
+        match Raster::<f32>::eval("(a + 1", files.clone()) {
+            Err(RasterError::Expression{ position, .. }) => assert_eq!(position, 6),
+            other => panic!("Expected an expression error, got {:?}", other),
+        }
+        assert!(Raster::<f32>::eval("a $ 2", files.clone()).is_err());
+        assert!(Raster::<f32>::eval("a 2", files.clone()).is_err());
+        assert!(Raster::<f32>::eval("1 + 2", files).is_err());
+    }
+}
@@ -0,0 +1,134 @@
+//! Calls into the GDAL C API for what the `gdal` crate does not wrap yet.
+
+use std::ffi::{CStr, CString};
+use std::marker::PhantomData;
+use std::process;
+use std::ptr::{null, null_mut};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use gdal::errors::{Error as GdalError, ErrorKind};
+use gdal::raster::Dataset;
+use gdal_sys::{self, CPLErr, GDALDataType, GDALResampleAlg};
+
+fn last_cpl_error(class: CPLErr::Type) -> GdalError {
+    let (number, msg) = unsafe {
+        let number = gdal_sys::CPLGetLastErrorNo();
+        let msg = CStr::from_ptr(gdal_sys::CPLGetLastErrorMsg()).to_string_lossy().into_owned();
+        gdal_sys::CPLErrorReset();
+        (number, msg)
+    };
+    ErrorKind::CplError{ class, number, msg }.into()
+}
+
+pub fn set_nodata(dataset: &Dataset, band: isize, nodata: f64) -> Result<(), GdalError>{
+    let rv = unsafe {
+        let c_band = gdal_sys::GDALGetRasterBand(dataset._c_ptr(), band as i32);
+        gdal_sys::GDALSetRasterNoDataValue(c_band, nodata)
+    };
+    if rv != CPLErr::CE_None {
+        return Err(last_cpl_error(rv));
+    }
+    Ok(())
+}
+
+/// The `KEY=VALUE` items of a metadata domain of the dataset, "" being the
+/// default domain.
+pub fn metadata(dataset: &Dataset, domain: &str) -> Result<Vec<String>, GdalError>{
+    let domain = CString::new(domain)?;
+    let mut items = Vec::new();
+
+    unsafe {
+        let list = gdal_sys::GDALGetMetadata(dataset._c_ptr(), domain.as_ptr());
+        if list.is_null() {
+            return Ok(items);
+        }
+        let mut index = 0;
+        while !(*list.offset(index)).is_null() {
+            items.push(CStr::from_ptr(*list.offset(index)).to_string_lossy().into_owned());
+            index += 1;
+        }
+    }
+    Ok(items)
+}
+
+/// Name GDAL gives to a pixel data type, such as "Float32".
+pub fn data_type_name(data_type: GDALDataType::Type) -> String{
+    let name = unsafe { gdal_sys::GDALGetDataTypeName(data_type) };
+    if name.is_null() {
+        return String::new();
+    }
+    unsafe { CStr::from_ptr(name) }.to_string_lossy().into_owned()
+}
+
+/// Geotransform, columns and rows GDAL suggests to warp `dataset` into the
+/// spatial reference `wkt`.
+pub fn suggested_warp_output(dataset: &Dataset, wkt: &str) -> Result<([f64; 6], usize, usize), GdalError>{
+    let wkt = CString::new(wkt)?;
+    let mut geotransform = [0.0; 6];
+    let (mut cols, mut rows) = (0, 0);
+
+    let rv = unsafe {
+        let transformer = gdal_sys::GDALCreateGenImgProjTransformer(dataset._c_ptr(), null(), null_mut(), wkt.as_ptr(), 0, 0.0, 0);
+        if transformer.is_null() {
+            return Err(last_cpl_error(CPLErr::CE_Failure));
+        }
+        let rv = gdal_sys::GDALSuggestedWarpOutput(dataset._c_ptr(), Some(gdal_sys::GDALGenImgProjTransform), transformer,
+                                                   geotransform.as_mut_ptr(), &mut cols, &mut rows);
+        gdal_sys::GDALDestroyGenImgProjTransformer(transformer);
+        rv
+    };
+    if rv != CPLErr::CE_None {
+        return Err(last_cpl_error(rv));
+    }
+    Ok((geotransform, cols as usize, rows as usize))
+}
+
+/// Warps `src` into the grid and spatial reference of `dst` with `alg`, which
+/// `gdal::raster::reproject` fixes to bilinear.
+pub fn reproject_image(src: &Dataset, dst: &Dataset, alg: GDALResampleAlg::Type) -> Result<(), GdalError>{
+    let rv = unsafe {
+        gdal_sys::GDALReprojectImage(src._c_ptr(), null(), dst._c_ptr(), null(), alg, 0.0, 0.0, None, null_mut(), null_mut())
+    };
+    if rv != CPLErr::CE_None {
+        return Err(last_cpl_error(rv));
+    }
+    Ok(())
+}
+
+static MEM_FILES: AtomicUsize = AtomicUsize::new(0);
+
+/// A `/vsimem/` file backed by borrowed bytes, which GDAL reads in place. It
+/// is unlinked when dropped.
+pub struct MemFile<'a> {
+    path: CString,
+    bytes: PhantomData<&'a [u8]>
+}
+
+impl<'a> MemFile<'a> {
+    /// Registers `bytes` under a unique path ending with `.ext`.
+    pub fn new(bytes: &'a [u8], ext: &str) -> Result<MemFile<'a>, GdalError>{
+        let number = MEM_FILES.fetch_add(1, Ordering::SeqCst);
+        let path = CString::new(format!("/vsimem/raster_mapping_{}_{}.{}", process::id(), number, ext.trim_start_matches('.')))?;
+
+        unsafe {
+            // GDAL does not write to the buffer of a file it does not own.
+            let file = gdal_sys::VSIFileFromMemBuffer(path.as_ptr(), bytes.as_ptr() as *mut u8, bytes.len() as u64, 0);
+            if file.is_null() {
+                return Err(last_cpl_error(CPLErr::CE_Failure));
+            }
+            gdal_sys::VSIFCloseL(file);
+        }
+        Ok(MemFile{ path, bytes: PhantomData })
+    }
+
+    pub fn path(&self) -> &str{
+        self.path.to_str().expect("the path is built from UTF-8")
+    }
+}
+
+impl<'a> Drop for MemFile<'a> {
+    fn drop(&mut self){
+        unsafe {
+            gdal_sys::VSIUnlink(self.path.as_ptr());
+        }
+    }
+}
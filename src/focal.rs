@@ -0,0 +1,448 @@
+use std::cmp::Ordering;
+use std::ops::Range;
+use ndarray::{Array2, Axis};
+use rayon;
+use rayon::prelude::*;
+use super::Raster;
+
+fn mean(values: &[f32]) -> f32{
+    (values.iter().map(|&value| f64::from(value)).sum::<f64>() / values.len() as f64) as f32
+}
+
+/// Convolves each row, or each column along `Axis(0)`, with the symmetric
+/// `kernel` of odd length, leaving out what falls outside the array.
+fn convolve_axis(data: &Array2<f64>, kernel: &[f64], axis: Axis) -> Array2<f64>{
+    let radius = (kernel.len() / 2) as isize;
+    let length = data.len_of(axis) as isize;
+
+    Array2::from_shape_fn(data.dim(), |(row, col)| {
+        let position = if axis == Axis(0) { row } else { col } as isize;
+        (-radius..=radius)
+            .filter(|offset| (0..length).contains(&(position + offset)))
+            .map(|offset| {
+                let index = if axis == Axis(0) {
+                    [(row as isize + offset) as usize, col]
+                } else {
+                    [row, (col as isize + offset) as usize]
+                };
+                kernel[(offset + radius) as usize] * data[index]
+            })
+            .sum()
+    })
+}
+
+impl Raster<f32> {
+    /// Applies `f` to the valid pixels of the `(2 * radius + 1)` square window
+    /// around each valid pixel of `rows`, returning them in row-major order.
+    /// The window is cut at the edges, so `f` only sees in-bounds pixels and
+    /// never an empty slice. Pixels equal to `nodata` are kept as they are.
+    fn focal_rows<F>(&self, rows: Range<usize>, radius: usize, nodata: Option<f32>, f: &F) -> Vec<f32>
+        where F: Fn(&[f32]) -> f32
+    {
+        let (height, cols) = self.data.dim();
+        let mut values = Vec::with_capacity((2 * radius + 1).pow(2));
+
+        let mut data = Vec::with_capacity(rows.len() * cols);
+        for row in rows {
+            for col in 0..cols {
+                let value = self.data[[row, col]];
+                if Some(value) == nodata {
+                    data.push(value);
+                    continue;
+                }
+
+                let window = self.data.slice(s![
+                    row.saturating_sub(radius)..(row + radius + 1).min(height),
+                    col.saturating_sub(radius)..(col + radius + 1).min(cols)
+                ]);
+                values.clear();
+                values.extend(window.iter().cloned().filter(|&value| Some(value) != nodata));
+                data.push(f(&values));
+            }
+        }
+        data
+    }
+
+    fn focal_reduce<F>(&self, radius: usize, nodata: Option<f32>, f: F) -> Raster<f32>
+        where F: Fn(&[f32]) -> f32
+    {
+        let (rows, cols) = self.data.dim();
+        let data = self.focal_rows(0..rows, radius, nodata, &f);
+        self.with_data(Array2::from_shape_vec((rows, cols), data).expect("one value per pixel"))
+    }
+
+    /// `focal_reduce` on horizontal bands of rows processed on the rayon thread
+    /// pool, one band per thread. The windows of a band read up to `radius`
+    /// rows of its neighbours, so the result is identical to the sequential one.
+    fn par_focal_reduce<F>(&self, radius: usize, nodata: Option<f32>, f: F) -> Raster<f32>
+        where F: Fn(&[f32]) -> f32 + Sync
+    {
+        let (rows, cols) = self.data.dim();
+        let band = rows.div_ceil(rayon::current_num_threads()).max(1);
+        let starts: Vec<usize> = (0..rows).step_by(band).collect();
+
+        let bands: Vec<Vec<f32>> = starts.into_par_iter()
+            .map(|start| self.focal_rows(start..(start + band).min(rows), radius, nodata, &f))
+            .collect();
+        self.with_data(Array2::from_shape_vec((rows, cols), bands.concat()).expect("one value per pixel"))
+    }
+
+    /// Mean of the `(2 * radius + 1)` square window around each pixel, leaving
+    /// out the pixels equal to `nodata`. At the edges only the in-bounds
+    /// pixels are averaged. NoData pixels are kept as they are.
+    pub fn focal_mean(&self, radius: usize, nodata: Option<f32>) -> Raster<f32>{
+        self.focal_reduce(radius, nodata, mean)
+    }
+
+    /// `focal_mean` running on all the rayon threads, for large rasters.
+    pub fn par_focal_mean(&self, radius: usize, nodata: Option<f32>) -> Raster<f32>{
+        self.par_focal_reduce(radius, nodata, mean)
+    }
+
+    /// Applies `f` to the `(2 * radius + 1)` square window around each pixel,
+    /// as `focal_mean` does, with the raster's NoData value.
+    pub fn focal<F>(&self, radius: usize, f: F) -> Raster<f32>
+        where F: Fn(&[f32]) -> f32
+    {
+        self.focal_reduce(radius, self.nodata_value(), f)
+    }
+
+    /// `focal` running on all the rayon threads, for large rasters.
+    pub fn par_focal<F>(&self, radius: usize, f: F) -> Raster<f32>
+        where F: Fn(&[f32]) -> f32 + Sync
+    {
+        self.par_focal_reduce(radius, self.nodata_value(), f)
+    }
+
+    /// Median of the window around each pixel, the mean of the two middle
+    /// values for an even count, see `focal`.
+    pub fn focal_median(&self, radius: usize) -> Raster<f32>{
+        self.focal(radius, |values| {
+            let mut sorted = values.to_vec();
+            sorted.sort_by(|a, b| a.partial_cmp(b).unwrap_or(Ordering::Equal));
+            let middle = sorted.len() / 2;
+            if sorted.len() % 2 == 0 {
+                (sorted[middle - 1] + sorted[middle]) / 2.0
+            } else {
+                sorted[middle]
+            }
+        })
+    }
+
+    /// Maximum of the window around each pixel, see `focal`.
+    pub fn focal_max(&self, radius: usize) -> Raster<f32>{
+        self.focal(radius, |values| values.iter().cloned().fold(f32::NEG_INFINITY, f32::max))
+    }
+
+    /// Replaces the pixels equal to `nodata` with the value of a valid pixel at
+    /// most `max_distance` pixels away, counting diagonal steps as one. Each
+    /// pass fills the NoData pixels next to a valid one, preferring the
+    /// neighbors above, left, right and below, in this order, to the diagonal
+    /// ones. Pixels farther than `max_distance` stay NoData.
+    pub fn fill_nodata(&self, nodata: f32, max_distance: usize) -> Raster<f32>{
+        const NEIGHBORS: [(isize, isize); 8] = [(-1, 0), (0, -1), (0, 1), (1, 0), (-1, -1), (-1, 1), (1, -1), (1, 1)];
+
+        let (rows, cols) = self.data.dim();
+        let mut data = self.data.clone();
+
+        for _ in 0..max_distance {
+            let previous = data.clone();
+            let mut filled = false;
+
+            for ((row, col), item) in data.indexed_iter_mut() {
+                if *item != nodata {
+                    continue;
+                }
+                let neighbor = NEIGHBORS.iter()
+                    .map(|&(dr, dc)| (row as isize + dr, col as isize + dc))
+                    .filter(|&(r, c)| r >= 0 && c >= 0 && (r as usize) < rows && (c as usize) < cols)
+                    .map(|(r, c)| previous[[r as usize, c as usize]])
+                    .find(|&value| value != nodata);
+                if let Some(value) = neighbor {
+                    *item = value;
+                    filled = true;
+                }
+            }
+
+            if !filled {
+                break;
+            }
+        }
+        self.with_data(data)
+    }
+
+    /// Smooths with a Gaussian kernel of standard deviation `sigma` pixels,
+    /// cut at `3 * sigma`, convolving the rows and then the columns. The
+    /// weights are renormalized over the valid pixels, leaving out those equal
+    /// to `nodata` and those outside the raster, so the edges and the
+    /// neighbors of NoData are not darkened. NoData pixels are kept as they
+    /// are.
+    ///
+    /// Panics if `sigma` is not positive.
+    pub fn gaussian_blur(&self, sigma: f32, nodata: Option<f32>) -> Raster<f32>{
+        assert!(sigma > 0.0, "The standard deviation must be positive");
+
+        let sigma = f64::from(sigma);
+        let radius = (3.0 * sigma).ceil() as isize;
+        let kernel: Vec<f64> = (-radius..=radius).map(|offset| (-(offset * offset) as f64 / (2.0 * sigma * sigma)).exp()).collect();
+
+        // Blurring the valid values and the valid mask alike gives the weight
+        // of the valid pixels in each window to divide by.
+        let valid = self.data.mapv(|value| if Some(value) == nodata { 0.0 } else { 1.0 });
+        let values = self.data.mapv(|value| if Some(value) == nodata { 0.0 } else { f64::from(value) });
+        let values = convolve_axis(&convolve_axis(&values, &kernel, Axis(1)), &kernel, Axis(0));
+        let weights = convolve_axis(&convolve_axis(&valid, &kernel, Axis(1)), &kernel, Axis(0));
+
+        let mut data = self.data.clone();
+        for ((item, &value), &weight) in data.iter_mut().zip(&values).zip(&weights) {
+            if Some(*item) != nodata {
+                *item = (value / weight) as f32;
+            }
+        }
+        self.with_data(data)
+    }
+
+    /// Weighted sum of the window around each pixel with the weights of
+    /// `kernel`, centered on the pixel and laid out as the window, so
+    /// `kernel[[0, 0]]` weights the neighbor above and to the left. The
+    /// neighbors outside the raster or equal to `nodata` are left out. When
+    /// the weights of the kernel do not sum to zero, the sum is renormalized
+    /// by the weights of the pixels left in, as `gaussian_blur` does, as if
+    /// the pixels left out had the weighted mean of the others, and it is NaN
+    /// where these weights sum to zero. Zero-sum kernels, such as edge
+    /// detectors, are not renormalized. NoData pixels are kept as they are.
+    ///
+    /// Panics if the kernel dimensions are not odd.
+    pub fn convolve(&self, kernel: &Array2<f32>, nodata: Option<f32>) -> Raster<f32>{
+        let (height, width) = kernel.dim();
+        assert!(height % 2 == 1 && width % 2 == 1, "The kernel dimensions must be odd");
+
+        let (rows, cols) = self.data.dim();
+        let (radius_rows, radius_cols) = ((height / 2) as isize, (width / 2) as isize);
+        let total: f64 = kernel.iter().map(|&weight| f64::from(weight)).sum();
+
+        let mut data = self.data.clone();
+        for ((row, col), item) in data.indexed_iter_mut() {
+            if Some(*item) == nodata {
+                continue;
+            }
+
+            let (mut sum, mut weights) = (0.0, 0.0);
+            for ((kr, kc), &weight) in kernel.indexed_iter() {
+                let (r, c) = (row as isize + kr as isize - radius_rows, col as isize + kc as isize - radius_cols);
+                if r < 0 || c < 0 || r as usize >= rows || c as usize >= cols {
+                    continue;
+                }
+                let value = self.data[[r as usize, c as usize]];
+                if Some(value) != nodata {
+                    sum += f64::from(weight) * f64::from(value);
+                    weights += f64::from(weight);
+                }
+            }
+            *item = if total == 0.0 {
+                sum as f32
+            } else if weights == 0.0 {
+                f32::NAN
+            } else {
+                (sum * total / weights) as f32
+            };
+        }
+        self.with_data(data)
+    }
+}
+
+#[cfg(test)]
+mod test_focal {
+    use super::*;
+
+    /// Scattered values with some NoData pixels, without a random generator.
+    fn scattered(rows: usize, cols: usize) -> Raster<f32> {
+        let mut raster = Raster::<f32>::from(Array2::from_shape_fn((rows, cols), |(row, col)| {
+            let hash = (row * 7919 + col * 104729) % 1009;
+            if hash % 97 == 0 { -9999.0 } else { hash as f32 / 10.0 }
+        }));
+        raster.nodata = Some(-9999.0);
+        raster
+    }
+
+    #[test]
+    fn test_par_focal_mean(){
+        let raster = scattered(203, 151);
+
+        for &radius in &[0, 1, 3] {
+            assert_eq!(raster.par_focal_mean(radius, Some(-9999.0)), raster.focal_mean(radius, Some(-9999.0)));
+        }
+        let minimum = |values: &[f32]| values.iter().cloned().fold(f32::INFINITY, f32::min);
+        assert_eq!(raster.par_focal(2, minimum), raster.focal(2, minimum));
+    }
+
+    #[test]
+    fn test_par_focal_small(){
+        let raster = scattered(1, 5);
+        assert_eq!(raster.par_focal_mean(2, None), raster.focal_mean(2, None));
+
+        let empty = Raster::<f32>::from(Array2::zeros((0, 3)));
+        assert_eq!(empty.par_focal_mean(1, None).data.dim(), (0, 3));
+    }
+
+    #[test]
+    fn test_gaussian_blur_spike(){
+        let mut spike = Raster::<f32>::from(Array2::zeros((15, 15)));
+        spike.data[[7, 7]] = 1.0;
+        let blurred = spike.gaussian_blur(1.0, None);
+
+        assert_relative_eq!(blurred.data.iter().sum::<f32>(), 1.0, epsilon = 1e-5);
+        assert!(blurred[(7, 7)] < 1.0);
+        assert!(blurred[(7, 7)] > blurred[(7, 8)]);
+        for offset in 1..4 {
+            assert_relative_eq!(blurred[(7 - offset, 7)], blurred[(7 + offset, 7)]);
+            assert_relative_eq!(blurred[(7, 7 - offset)], blurred[(7 + offset, 7)]);
+            assert_relative_eq!(blurred[(7 - offset, 7 - offset)], blurred[(7 + offset, 7 + offset)]);
+        }
+        assert_eq!(blurred[(7, 11)], 0.0);
+    }
+
+    #[test]
+    fn test_gaussian_blur_renormalized(){
+        let mut raster = Raster::<f32>::from(Array2::from_elem((5, 6), 2.0));
+        raster.data[[2, 2]] = -9999.0;
+        let blurred = raster.gaussian_blur(1.5, Some(-9999.0));
+
+        // A constant stays constant at the edges and around NoData.
+        for (index, &value) in blurred.data.indexed_iter() {
+            if index == (2, 2) {
+                assert_eq!(value, -9999.0);
+            } else {
+                assert_relative_eq!(value, 2.0, epsilon = 1e-5);
+            }
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "The standard deviation must be positive")]
+    fn test_gaussian_blur_sigma(){
+        Raster::<f32>::from(Array2::zeros((2, 2))).gaussian_blur(0.0, None);
+    }
+
+    #[test]
+    fn test_convolve_sobel(){
+        // A step from 0 to 10 between the columns 2 and 3.
+        let raster = Raster::<f32>::from(Array2::from_shape_fn((4, 6), |(_, col)| if col < 3 { 0.0 } else { 10.0 }));
+        let sobel = array![[-1.0, 0.0, 1.0], [-2.0, 0.0, 2.0], [-1.0, 0.0, 1.0]];
+        let edges = raster.convolve(&sobel, None);
+
+        for row in 1..3 {
+            assert_eq!(edges.row(row).slice(s![1..5]), array![0.0, 40.0, 40.0, 0.0]);
+        }
+        // Without the row above, the response is not renormalized.
+        assert_eq!(edges[(0, 2)], 30.0);
+        // Turned a quarter, the kernel sees no step inside.
+        let rows = raster.convolve(&sobel.t().to_owned(), None);
+        assert_eq!(rows.data.slice(s![1..3, ..]), Array2::zeros((2, 6)));
+    }
+
+    #[test]
+    fn test_convolve_renormalized(){
+        let mut raster = Raster::<f32>::from(Array2::from_elem((4, 5), 3.0));
+        raster.data[[1, 1]] = -9999.0;
+        let smoothed = raster.convolve(&(array![[1.0, 2.0, 1.0], [2.0, 4.0, 2.0], [1.0, 2.0, 1.0]] / 16.0), Some(-9999.0));
+
+        // A constant stays constant at the edges and around NoData.
+        for (index, &value) in smoothed.data.indexed_iter() {
+            if index == (1, 1) {
+                assert_eq!(value, -9999.0);
+            } else {
+                assert_relative_eq!(value, 3.0, epsilon = 1e-6);
+            }
+        }
+
+        assert_eq!(raster.convolve(&array![[1.0]], Some(-9999.0)), raster);
+    }
+
+    #[test]
+    #[should_panic(expected = "The kernel dimensions must be odd")]
+    fn test_convolve_even_kernel(){
+        Raster::<f32>::from(Array2::zeros((3, 3))).convolve(&Array2::ones((2, 3)), None);
+    }
+
+    #[test]
+    fn test_focal_mean(){
+        let raster = Raster::<f32>::from(array![[1.0, 2.0, 3.0, 4.0],
+                                                [5.0, 6.0, 7.0, 8.0],
+                                                [9.0, 10.0, 11.0, 12.0]]);
+        let smoothed = raster.focal_mean(1, None);
+
+        assert_relative_eq!(smoothed.data[[1, 1]], 6.0);
+        assert_relative_eq!(smoothed.data[[1, 2]], 7.0);
+        // Corners average the 2x2 in-bounds pixels.
+        assert_relative_eq!(smoothed.data[[0, 0]], 3.5);
+        assert_relative_eq!(smoothed.data[[2, 3]], 9.5);
+    }
+
+    #[test]
+    fn test_focal_mean_nodata(){
+        let raster = Raster::<f32>::from(array![[1.0, -9999.0, 3.0],
+                                                [4.0, 5.0, 6.0],
+                                                [7.0, 8.0, 9.0]]);
+        let smoothed = raster.focal_mean(1, Some(-9999.0));
+
+        assert_relative_eq!(smoothed.data[[1, 1]], 43.0 / 8.0);
+        assert_relative_eq!(smoothed.data[[0, 0]], 10.0 / 3.0);
+        assert_eq!(smoothed.data[[0, 1]], -9999.0);
+    }
+
+    #[test]
+    fn test_focal_mean_zero_radius(){
+        let raster = Raster::<f32>::from(array![[1.0, 2.0], [3.0, 4.0]]);
+
+        assert_eq!(raster.focal_mean(0, None).data, raster.data);
+    }
+
+    #[test]
+    fn test_focal_range(){
+        let raster = Raster::<f32>::from(array![[1.0, 5.0, 2.0],
+                                                [4.0, 3.0, 9.0],
+                                                [0.0, 8.0, 6.0]]);
+        let range = raster.focal(1, |values| {
+            let max = values.iter().cloned().fold(f32::NEG_INFINITY, f32::max);
+            let min = values.iter().cloned().fold(f32::INFINITY, f32::min);
+            max - min
+        });
+
+        assert_eq!(range.data, array![[4.0, 8.0, 7.0], [8.0, 9.0, 7.0], [8.0, 9.0, 6.0]]);
+    }
+
+    #[test]
+    fn test_focal_median_max(){
+        let mut raster = Raster::<f32>::from(array![[1.0, 5.0, 2.0],
+                                                    [4.0, 3.0, 9.0],
+                                                    [0.0, 8.0, -9999.0]]);
+        raster.nodata = Some(-9999.0);
+
+        assert_eq!(raster.focal_median(1).data, array![[3.5, 3.5, 4.0], [3.5, 3.5, 5.0], [3.5, 4.0, -9999.0]]);
+        assert_eq!(raster.focal_max(1).data, array![[5.0, 9.0, 9.0], [8.0, 9.0, 9.0], [8.0, 9.0, -9999.0]]);
+    }
+
+    #[test]
+    fn test_fill_nodata(){
+        let raster = Raster::<f32>::from(array![[1.0, 2.0, 3.0, -1.0, -1.0, -1.0, -1.0],
+                                                [4.0, -1.0, 6.0, -1.0, -1.0, -1.0, -1.0],
+                                                [7.0, 8.0, 9.0, -1.0, -1.0, -1.0, -1.0]]);
+        let filled = raster.fill_nodata(-1.0, 2);
+
+        // The interior hole takes the value above it.
+        assert_eq!(filled.data[[1, 1]], 2.0);
+        assert_eq!(filled.data.slice(s![.., 3]).to_vec(), vec![3.0, 6.0, 9.0]);
+        assert_eq!(filled.data.slice(s![.., 4]).to_vec(), vec![3.0, 6.0, 9.0]);
+        // Three pixels away from any valid one.
+        assert_eq!(filled.data.slice(s![.., 5]).to_vec(), vec![-1.0; 3]);
+    }
+
+    #[test]
+    fn test_fill_nodata_diagonal(){
+        let raster = Raster::<f32>::from(array![[5.0, -1.0], [-1.0, -1.0]]);
+
+        assert_eq!(raster.fill_nodata(-1.0, 1).data, array![[5.0, 5.0], [5.0, 5.0]]);
+        assert_eq!(raster.fill_nodata(-1.0, 0).data, raster.data);
+    }
+}
@@ -0,0 +1,277 @@
+use ndarray::Array2;
+use super::{window_geotransform, Raster};
+
+/// How `Raster::sample` reads a value between pixel centers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Interp {
+    /// The pixel containing the point.
+    Nearest,
+    /// The four pixel centers around the point, weighted by distance.
+    Bilinear,
+}
+
+impl Raster<f32> {
+    /// Fractional `(row, col)` of the world point `(x, y)`, where pixel
+    /// `(0, 0)` spans `[0, 1)` on both axes.
+    fn world_to_fractional(&self, x: f64, y: f64) -> (f64, f64){
+        let gt = self.geotransform;
+        let (dx, dy) = (x - gt[0], y - gt[3]);
+        let determinant = gt[1] * gt[5] - gt[2] * gt[4];
+
+        ((gt[1] * dy - gt[4] * dx) / determinant, (gt[5] * dx - gt[2] * dy) / determinant)
+    }
+
+    /// The `(row, col)` of the pixel containing the world point `(x, y)`,
+    /// which can be outside the raster. Rotated geotransforms are inverted too.
+    pub fn world_to_pixel(&self, x: f64, y: f64) -> (isize, isize){
+        let (row, col) = self.world_to_fractional(x, y);
+        (row.floor() as isize, col.floor() as isize)
+    }
+
+    /// Value at the world point `(x, y)`, or `None` outside the raster or when
+    /// a pixel used is NoData. Within half a pixel of the edges, `Bilinear`
+    /// only blends along the axis that has two pixel centers around the point.
+    pub fn sample(&self, x: f64, y: f64, interp: Interp) -> Option<f32>{
+        let (rows, cols) = self.data.dim();
+        let (row, col) = self.world_to_fractional(x, y);
+        if !(row >= 0.0 && row < rows as f64 && col >= 0.0 && col < cols as f64) {
+            return None;
+        }
+
+        let nodata = self.nodata_value();
+        let valid = |value: f32| if Some(value) == nodata { None } else { Some(value) };
+
+        match interp {
+            Interp::Nearest => valid(self.data[[row as usize, col as usize]]),
+            Interp::Bilinear => {
+                // Position relative to the pixel centers.
+                let (row, col) = (row - 0.5, col - 0.5);
+                let (row0, col0) = (row.floor(), col.floor());
+                let (fy, fx) = (row - row0, col - col0);
+                let clamp = |index: f64, total: usize| index.max(0.0).min(total as f64 - 1.0) as usize;
+                let (top, bottom) = (clamp(row0, rows), clamp(row0 + 1.0, rows));
+                let (left, right) = (clamp(col0, cols), clamp(col0 + 1.0, cols));
+
+                // Pixels without weight are skipped, so a NoData neighbor
+                // does not spoil a point on a pixel center line.
+                let mut value = 0.0;
+                for &(row, weight_y) in &[(top, 1.0 - fy), (bottom, fy)] {
+                    for &(col, weight_x) in &[(left, 1.0 - fx), (right, fx)] {
+                        let weight = weight_y * weight_x;
+                        if weight > 0.0 {
+                            value += weight * f64::from(valid(self.data[[row, col]])?);
+                        }
+                    }
+                }
+                Some(value as f32)
+            }
+        }
+    }
+
+    /// World coordinates `(x, y)` of the center of the pixel at `(row, col)`.
+    pub fn pixel_to_world(&self, row: usize, col: usize) -> (f64, f64){
+        let gt = self.geotransform;
+        let (row, col) = (row as f64 + 0.5, col as f64 + 0.5);
+
+        (gt[0] + col * gt[1] + row * gt[2], gt[3] + col * gt[4] + row * gt[5])
+    }
+
+    /// Extent of the raster in world coordinates as `(min_x, min_y, max_x,
+    /// max_y)`, from the outer edges of the pixels. With a rotated
+    /// geotransform it is the box around the four corners.
+    pub fn bounds(&self) -> (f64, f64, f64, f64){
+        let gt = self.geotransform;
+        let (rows, cols) = self.data.dim();
+        let corners = [(0.0, 0.0), (0.0, cols as f64), (rows as f64, 0.0), (rows as f64, cols as f64)];
+
+        corners.iter()
+            .map(|&(row, col)| (gt[0] + col * gt[1] + row * gt[2], gt[3] + col * gt[4] + row * gt[5]))
+            .fold((f64::INFINITY, f64::INFINITY, f64::NEG_INFINITY, f64::NEG_INFINITY), |bounds, (x, y)| {
+                (bounds.0.min(x), bounds.1.min(y), bounds.2.max(x), bounds.3.max(y))
+            })
+    }
+
+    /// Copies the pixels overlapping the box from `(min_x, min_y)` to
+    /// `(max_x, max_y)` in world coordinates, for a geotransform without
+    /// rotation. Pixels partly inside the box are kept. The box is clamped to
+    /// the raster extent, so a box outside the raster gives an empty raster.
+    /// The origin of the geotransform is moved to the crop corner.
+    pub fn crop_bbox(&self, min_x: f64, min_y: f64, max_x: f64, max_y: f64) -> Raster<f32>{
+        let gt = self.geotransform;
+        let (rows, cols) = self.data.dim();
+
+        let range = |start: f64, end: f64, origin: f64, size: f64, total: usize| {
+            let (first, second) = ((start - origin) / size, (end - origin) / size);
+            let low = first.min(second).floor().max(0.0).min(total as f64) as usize;
+            let high = first.max(second).ceil().max(0.0).min(total as f64) as usize;
+            (low, high.max(low))
+        };
+        let (col_start, col_end) = range(min_x, max_x, gt[0], gt[1], cols);
+        let (row_start, row_end) = range(min_y, max_y, gt[3], gt[5], rows);
+
+        let mut raster = self.with_data(self.data.slice(s![row_start..row_end, col_start..col_end]).to_owned());
+        raster.geotransform = window_geotransform(gt, (col_start as isize, row_start as isize));
+        raster
+    }
+
+    /// Surrounds the raster with a border of `fill`, such as the NoData value,
+    /// `top` rows above, `bottom` below, `left` columns before and `right`
+    /// after. The origin of the geotransform is moved to the new corner, so
+    /// the original pixels keep their world coordinates.
+    pub fn pad(&self, top: usize, bottom: usize, left: usize, right: usize, fill: f32) -> Raster<f32>{
+        let (rows, cols) = self.data.dim();
+        let mut data = Array2::from_elem((top + rows + bottom, left + cols + right), fill);
+        data.slice_mut(s![top..top + rows, left..left + cols]).assign(&self.data);
+
+        let mut raster = self.with_data(data);
+        raster.geotransform = window_geotransform(self.geotransform, (-(left as isize), -(top as isize)));
+        raster
+    }
+}
+
+#[cfg(test)]
+mod test_georef {
+    use super::*;
+
+    #[test]
+    fn test_crop_bbox(){
+        let raster = Raster{
+            nodata: Some(-9999.0),
+            geotransform: [10.0, 2.0, 0.0, 20.0, 0.0, -2.0],
+            ..Raster::from(Array2::from_shape_fn((4, 5), |(row, col)| (10 * row + col) as f32))
+        };
+        let cropped = raster.crop_bbox(12.0, 14.0, 16.0, 18.0);
+
+        assert_eq!(cropped.data, array![[11.0, 12.0], [21.0, 22.0]]);
+        assert_eq!(cropped.geotransform, [12.0, 2.0, 0.0, 18.0, 0.0, -2.0]);
+        assert_eq!(cropped.nodata, Some(-9999.0));
+    }
+
+    #[test]
+    fn test_crop_bbox_partial_pixels(){
+        let raster = Raster{
+            nodata: Some(-9999.0),
+            geotransform: [10.0, 2.0, 0.0, 20.0, 0.0, -2.0],
+            ..Raster::from(Array2::from_shape_fn((4, 5), |(row, col)| (10 * row + col) as f32))
+        };
+        let cropped = raster.crop_bbox(13.0, 15.0, 15.0, 17.0);
+
+        assert_eq!(cropped.data, array![[11.0, 12.0], [21.0, 22.0]]);
+    }
+
+    #[test]
+    fn test_crop_bbox_clamped(){
+        let raster = Raster{
+            nodata: Some(-9999.0),
+            geotransform: [10.0, 2.0, 0.0, 20.0, 0.0, -2.0],
+            ..Raster::from(Array2::from_shape_fn((4, 5), |(row, col)| (10 * row + col) as f32))
+        };
+        let cropped = raster.crop_bbox(0.0, 0.0, 14.0, 100.0);
+
+        assert_eq!(cropped.data.dim(), (4, 2));
+        assert_eq!(cropped.geotransform, raster.geotransform);
+        assert_eq!(raster.crop_bbox(100.0, 100.0, 200.0, 200.0).data.len(), 0);
+    }
+
+    #[test]
+    fn test_pad(){
+        let raster = Raster{
+            nodata: Some(-9999.0),
+            geotransform: [10.0, 2.0, 0.0, 20.0, 0.0, -2.0],
+            ..Raster::<f32>::from(array![[1.0, 2.0], [3.0, 4.0]])
+        };
+        let padded = raster.pad(1, 1, 1, 1, -9999.0);
+
+        assert_eq!(padded.data, array![[-9999.0, -9999.0, -9999.0, -9999.0],
+                                       [-9999.0, 1.0, 2.0, -9999.0],
+                                       [-9999.0, 3.0, 4.0, -9999.0],
+                                       [-9999.0, -9999.0, -9999.0, -9999.0]]);
+        assert_eq!(padded.geotransform, [8.0, 2.0, 0.0, 22.0, 0.0, -2.0]);
+        assert_eq!(padded.pixel_to_world(1, 1), raster.pixel_to_world(0, 0));
+        assert_eq!(padded.nodata, Some(-9999.0));
+    }
+
+    #[test]
+    fn test_pad_uneven(){
+        let raster = Raster{
+            nodata: Some(-9999.0),
+            geotransform: [10.0, 2.0, 0.0, 20.0, 0.0, -2.0],
+            ..Raster::from(Array2::from_shape_fn((4, 5), |(row, col)| (10 * row + col) as f32))
+        };
+        let padded = raster.pad(0, 2, 3, 0, 0.0);
+
+        assert_eq!(padded.data.dim(), (6, 8));
+        assert_eq!(padded.data.slice(s![..4, 3..]), raster.data);
+        assert_eq!(padded.crop_bbox(10.0, 12.0, 20.0, 20.0), raster);
+    }
+
+    #[test]
+    fn test_pixel_to_world(){
+        let raster = Raster{
+            nodata: Some(-9999.0),
+            geotransform: [10.0, 2.0, 0.0, 20.0, 0.0, -2.0],
+            ..Raster::from(Array2::from_shape_fn((4, 5), |(row, col)| (10 * row + col) as f32))
+        };
+        assert_eq!(raster.pixel_to_world(0, 0), (11.0, 19.0));
+        assert_eq!(raster.pixel_to_world(3, 4), (19.0, 13.0));
+        assert_eq!(raster.world_to_pixel(11.0, 19.0), (0, 0));
+        assert_eq!(raster.world_to_pixel(9.0, 21.0), (-1, -1));
+    }
+
+    #[test]
+    fn test_bounds(){
+        let raster = Raster{
+            nodata: Some(-9999.0),
+            geotransform: [10.0, 2.0, 0.0, 20.0, 0.0, -2.0],
+            ..Raster::from(Array2::from_shape_fn((4, 5), |(row, col)| (10 * row + col) as f32))
+        };
+        assert_eq!(raster.bounds(), (10.0, 12.0, 20.0, 20.0));
+
+        let rotated = Raster{ geotransform: [100.0, 1.0, 0.5, 50.0, 0.25, -2.0], ..raster };
+        // Corners (100, 50), (105, 51.25), (102, 42) and (107, 43.25).
+        assert_eq!(rotated.bounds(), (100.0, 42.0, 107.0, 51.25));
+    }
+
+    #[test]
+    fn test_world_to_pixel_rotated(){
+        let raster = Raster{
+            geotransform: [100.0, 1.5, 0.5, 50.0, -0.25, -2.0],
+            ..Raster::<f32>::from(Array2::zeros((4, 5)))
+        };
+
+        for row in 0..4 {
+            for col in 0..5 {
+                let (x, y) = raster.pixel_to_world(row, col);
+                assert_eq!(raster.world_to_pixel(x, y), (row as isize, col as isize));
+            }
+        }
+    }
+
+    #[test]
+    fn test_sample(){
+        let raster = Raster{
+            nodata: Some(-9999.0),
+            geotransform: [0.0, 1.0, 0.0, 2.0, 0.0, -1.0],
+            ..Raster::<f32>::from(array![[1.0, 2.0], [3.0, -9999.0]])
+        };
+
+        assert_eq!(raster.sample(0.5, 1.5, Interp::Nearest), Some(1.0));
+        assert_eq!(raster.sample(0.5, 1.5, Interp::Bilinear), Some(1.0));
+        assert_eq!(raster.sample(1.0, 1.5, Interp::Bilinear), Some(1.5));
+        assert_eq!(raster.sample(0.5, 1.0, Interp::Bilinear), Some(2.0));
+        assert_eq!(raster.sample(1.9, 0.1, Interp::Nearest), None);
+        assert_eq!(raster.sample(1.0, 1.0, Interp::Bilinear), None);
+        assert_eq!(raster.sample(2.5, 1.0, Interp::Nearest), None);
+    }
+
+    #[test]
+    fn test_sample_bilinear(){
+        let raster = Raster{ geotransform: [0.0, 1.0, 0.0, 2.0, 0.0, -1.0], ..Raster::<f32>::from(array![[1.0, 2.0], [3.0, 4.0]]) };
+
+        assert_eq!(raster.sample(1.0, 1.0, Interp::Bilinear), Some(2.5));
+        // A quarter of the way from the center of (0, 0) to the center of (1, 1).
+        assert_eq!(raster.sample(0.75, 1.25, Interp::Bilinear), Some(1.75));
+        // Near the edge only one axis is blended.
+        assert_eq!(raster.sample(0.1, 1.0, Interp::Bilinear), Some(2.0));
+    }
+}
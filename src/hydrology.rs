@@ -0,0 +1,279 @@
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+use ndarray::Array2;
+use ordered_float::OrderedFloat;
+use super::{Raster, RasterError};
+
+/// D8 code of each neighbor with its `(row, col)` offset, clockwise from east.
+const D8: [(u8, isize, isize); 8] = [
+    (1, 0, 1), (2, 1, 1), (4, 1, 0), (8, 1, -1),
+    (16, 0, -1), (32, -1, -1), (64, -1, 0), (128, -1, 1),
+];
+
+/// D8 code of the pixels without a valid elevation.
+const D8_NODATA: u8 = 255;
+
+impl Raster<f32> {
+    /// D8 flow direction of a DEM: each pixel gets the code of its steepest
+    /// downslope neighbor, 1 east, 2 south-east, 4 south and so on clockwise
+    /// to 128 north-east. The drops are divided by the distances between the
+    /// pixel centers, from the pixel width and height of the geotransform, so
+    /// diagonal neighbors are farther. Only the neighbors inside the raster and
+    /// not equal to the NoData value count, so pits, flats and edge pixels
+    /// sloping outwards get 0. NoData and NaN pixels get 255, which is always
+    /// the NoData value of the result.
+    pub fn flow_direction(&self) -> Raster<u8>{
+        let nodata = self.nodata_value();
+        let (rows, cols) = self.data.dim();
+        let (width, height) = (self.geotransform[1].hypot(self.geotransform[4]), self.geotransform[2].hypot(self.geotransform[5]));
+
+        let data = Array2::from_shape_fn((rows, cols), |(row, col)| {
+            let elevation = self.data[[row, col]];
+            if Some(elevation) == nodata || elevation.is_nan() {
+                return D8_NODATA;
+            }
+
+            let mut direction = (0, 0.0);
+            for &(code, dr, dc) in &D8 {
+                let (r, c) = (row as isize + dr, col as isize + dc);
+                if r < 0 || c < 0 || r as usize >= rows || c as usize >= cols {
+                    continue;
+                }
+                let neighbor = self.data[[r as usize, c as usize]];
+                if Some(neighbor) == nodata || neighbor.is_nan() {
+                    continue;
+                }
+                let distance = (dc as f64 * width).hypot(dr as f64 * height);
+                let drop = f64::from(elevation - neighbor) / distance;
+                if drop > direction.1 {
+                    direction = (code, drop);
+                }
+            }
+            direction.0
+        });
+
+        let mut raster = self.with_data(data);
+        raster.nodata = Some(f64::from(D8_NODATA));
+        raster
+    }
+
+    /// Raises the pixels of closed depressions to the level of their spill
+    /// point with the priority-flood algorithm, so that every valid pixel has
+    /// a path to the edge of the raster or to a NoData pixel that never goes
+    /// up. Water leaves through the raster edges and the NoData pixels, which
+    /// are kept as they are, as are the NaN pixels. Flats are left flat.
+    pub fn fill_sinks(&self, nodata: Option<f32>) -> Raster<f32>{
+        let (rows, cols) = self.data.dim();
+        let invalid = |value: f32| Some(value) == nodata || value.is_nan();
+        let neighbors = |row: usize, col: usize| {
+            D8.iter()
+                .map(move |&(_, dr, dc)| (row as isize + dr, col as isize + dc))
+                .filter(move |&(r, c)| r >= 0 && c >= 0 && (r as usize) < rows && (c as usize) < cols)
+                .map(|(r, c)| (r as usize, c as usize))
+        };
+
+        let mut data = self.data.clone();
+        let mut done: Array2<bool> = data.mapv(invalid);
+        let mut queue = BinaryHeap::new();
+
+        // The outlets are the valid pixels on the edge or next to NoData.
+        for ((row, col), &value) in self.data.indexed_iter() {
+            let outlet = row == 0 || col == 0 || row == rows - 1 || col == cols - 1
+                || neighbors(row, col).any(|(r, c)| invalid(self.data[[r, c]]));
+            if !done[[row, col]] && outlet {
+                done[[row, col]] = true;
+                queue.push(Reverse((OrderedFloat(value), row, col)));
+            }
+        }
+
+        // Floods inwards from the lowest pixel reached so far.
+        while let Some(Reverse((OrderedFloat(level), row, col))) = queue.pop() {
+            for (r, c) in neighbors(row, col) {
+                if done[[r, c]] {
+                    continue;
+                }
+                done[[r, c]] = true;
+                data[[r, c]] = data[[r, c]].max(level);
+                queue.push(Reverse((OrderedFloat(data[[r, c]]), r, c)));
+            }
+        }
+        self.with_data(data)
+    }
+
+    /// Number of upstream pixels draining through each pixel of a DEM along
+    /// the D8 `direction` computed from it, see `flow_direction`.
+    ///
+    /// Panics if the shapes differ, see `try_flow_accumulation`.
+    pub fn flow_accumulation(&self, direction: &Raster<u8>) -> Raster<u32>{
+        self.try_flow_accumulation(direction).unwrap_or_else(|error| panic!("Cannot accumulate the flow: {}", error))
+    }
+
+    /// Flow accumulation, failing if the shapes differ. The pixels are visited
+    /// from the highest to the lowest, each one passing its count plus one to
+    /// the neighbor its direction points to, so a pixel is complete before it
+    /// drains. Flow pointing outside the raster or to a NoData pixel leaves
+    /// it. Pixels with the D8 NoData code 255 get `u32::MAX`, which is then
+    /// the NoData value.
+    pub fn try_flow_accumulation(&self, direction: &Raster<u8>) -> Result<Raster<u32>, RasterError>{
+        self.check_shape(direction)?;
+
+        let (rows, cols) = self.data.dim();
+        let mut order: Vec<(usize, usize)> = self.data.indexed_iter()
+            .filter(|&(index, _)| direction.data[index] != D8_NODATA)
+            .map(|(index, _)| index)
+            .collect();
+        order.sort_by(|&a, &b| OrderedFloat(self.data[b]).cmp(&OrderedFloat(self.data[a])));
+
+        let mut data: Array2<u32> = Array2::zeros((rows, cols));
+        for (row, col) in order {
+            let code = direction.data[[row, col]];
+            let target = D8.iter()
+                .find(|&&(d8, _, _)| d8 == code)
+                .map(|&(_, dr, dc)| (row as isize + dr, col as isize + dc))
+                .filter(|&(r, c)| r >= 0 && c >= 0 && (r as usize) < rows && (c as usize) < cols)
+                .map(|(r, c)| (r as usize, c as usize))
+                .filter(|&target| direction.data[target] != D8_NODATA);
+            if let Some(target) = target {
+                data[target] += data[[row, col]] + 1;
+            }
+        }
+
+        let nodata = direction.data.iter().any(|&code| code == D8_NODATA);
+        for (item, &code) in data.iter_mut().zip(direction.data.iter()) {
+            if code == D8_NODATA {
+                *item = u32::MAX;
+            }
+        }
+        let mut raster = self.with_data(data);
+        raster.nodata = if nodata || direction.nodata.is_some() { Some(f64::from(u32::MAX)) } else { None };
+        Ok(raster)
+    }
+}
+
+#[cfg(test)]
+mod test_hydrology {
+    use super::*;
+
+    #[test]
+    fn test_flow_direction_plane(){
+        // Inclined down to the east.
+        let east = Raster::from(Array2::from_shape_fn((3, 4), |(_, col)| (10 - col) as f32));
+        let direction = east.flow_direction();
+
+        assert_eq!(direction.data.slice(s![.., ..3]), Array2::from_elem((3, 3), 1));
+        assert_eq!(direction.data.column(3), array![0, 0, 0]);
+
+        // Inclined down to the south.
+        let south = Raster::from(Array2::from_shape_fn((4, 3), |(row, _)| (10 - row) as f32));
+        assert_eq!(south.flow_direction().data.row(1), array![4, 4, 4]);
+    }
+
+    #[test]
+    fn test_flow_direction_diagonal_distance(){
+        // The drop to the south-east is larger, but over a longer distance.
+        let raster = Raster::from(array![[10.0, 10.0, 10.0],
+                                         [10.0, 5.0, 1.0],
+                                         [10.0, 3.0, 0.0]]);
+        let direction = raster.flow_direction();
+
+        assert_eq!(direction.data[[1, 1]], 1);
+        assert_eq!(direction.data[[0, 0]], 2);
+        assert_eq!(direction.data[[2, 2]], 0);
+    }
+
+    #[test]
+    fn test_flow_direction_pit_and_nodata(){
+        let raster = Raster{
+            nodata: Some(-9999.0),
+            ..Raster::from(array![[5.0, 5.0, 5.0],
+                                  [5.0, 1.0, -9999.0],
+                                  [5.0, 5.0, 5.0]])
+        };
+        let direction = raster.flow_direction();
+
+        assert_eq!(direction.data[[1, 1]], 0);
+        assert_eq!(direction.data[[1, 2]], 255);
+        assert_eq!(direction.data[[0, 2]], 8);
+        assert_eq!(direction.nodata, Some(255.0));
+    }
+
+    #[test]
+    fn test_flow_direction_nan(){
+        let raster = Raster::<f32>::from(array![[3.0, 2.0, f32::NAN]]);
+        let direction = raster.flow_direction();
+
+        assert_eq!(direction.data, array![[1, 0, 255]]);
+        assert_eq!(direction.nodata, Some(255.0));
+    }
+
+    #[test]
+    fn test_fill_sinks_pit(){
+        let raster = Raster::from(array![[5.0, 5.0, 5.0, 5.0],
+                                         [5.0, 1.0, 6.0, 5.0],
+                                         [5.0, 6.0, 6.0, 5.0],
+                                         [5.0, 5.0, 5.0, 5.0]]);
+        let filled = raster.fill_sinks(Some(-9999.0));
+
+        assert_eq!(filled.data[[1, 1]], 5.0);
+        let mut expected = raster.data.clone();
+        expected[[1, 1]] = 5.0;
+        assert_eq!(filled.data, expected);
+        assert_eq!(filled.flow_direction().data[[1, 1]], 0);
+    }
+
+    #[test]
+    fn test_fill_sinks_basin(){
+        // The basin spills over its lowest rim pixel, at 3.
+        let raster = Raster::from(array![[9.0, 9.0, 9.0, 9.0, 9.0],
+                                         [9.0, 2.0, 1.0, 2.0, 9.0],
+                                         [9.0, 1.0, 0.0, 1.0, 3.0],
+                                         [9.0, 2.0, 1.0, 2.0, 9.0],
+                                         [9.0, 9.0, 9.0, 9.0, 9.0]]);
+        let filled = raster.fill_sinks(None);
+
+        assert_eq!(filled.data.slice(s![1..4, 1..4]), Array2::from_elem((3, 3), 3.0));
+        assert_eq!(filled.data.row(0), raster.data.row(0));
+    }
+
+    #[test]
+    fn test_fill_sinks_nodata_outlet(){
+        // The pit drains into the NoData pixel next to it.
+        let raster = Raster::from(array![[5.0, 5.0, 5.0, 5.0],
+                                         [5.0, 1.0, 4.0, 5.0],
+                                         [5.0, 4.0, -9999.0, 5.0],
+                                         [5.0, 5.0, 5.0, 5.0]]);
+
+        assert_eq!(raster.fill_sinks(Some(-9999.0)), raster);
+        assert_eq!(raster.fill_sinks(Some(-9999.0)).data[[2, 2]], -9999.0);
+    }
+
+    #[test]
+    fn test_flow_accumulation(){
+        // A valley draining south along the middle column to the outlet (3, 1).
+        let raster = Raster::from(array![[9.0, 8.0, 9.0],
+                                         [8.0, 6.0, 8.0],
+                                         [7.0, 4.0, 7.0],
+                                         [6.0, 1.0, 6.0]]);
+        let direction = raster.flow_direction();
+        let accumulation = raster.flow_accumulation(&direction);
+
+        assert_eq!(accumulation.data, array![[0, 0, 0], [0, 3, 0], [0, 6, 0], [0, 11, 0]]);
+        assert_eq!(accumulation.data[[3, 1]], 11);
+        assert_eq!(accumulation.nodata, Some(f64::from(u32::MAX)));
+    }
+
+    #[test]
+    fn test_flow_accumulation_nodata(){
+        let raster = Raster{ nodata: Some(-9999.0), ..Raster::from(array![[3.0, 2.0, 1.0, -9999.0]]) };
+        let accumulation = raster.flow_accumulation(&raster.flow_direction());
+
+        assert_eq!(accumulation.data, array![[0, 1, 2, u32::MAX]]);
+    }
+
+    #[test]
+    #[should_panic(expected = "Cannot accumulate the flow")]
+    fn test_flow_accumulation_mismatch(){
+        let raster = Raster::from(Array2::zeros((3, 3)));
+        raster.flow_accumulation(&Raster::<u8>::from(Array2::zeros((2, 2))));
+    }
+}
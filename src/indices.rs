@@ -0,0 +1,101 @@
+use std::path::Path;
+use itertools::zip;
+use ndarray::Array2;
+use super::{Mapping, Raster, RasterError};
+
+/// Normalized difference vegetation index, `(nir - red) / (nir + red)`, of two
+/// single band files. Panics if the files cannot be read or their shapes
+/// differ, see `try_ndvi`.
+pub fn ndvi<P: AsRef<Path>, Q: AsRef<Path>>(nir: P, red: Q, nodata: Option<f32>) -> Raster<f32>{
+    try_ndvi(nir, red, nodata).unwrap_or_else(|error| panic!("Could not compute the NDVI: {}", error))
+}
+
+/// Normalized difference vegetation index of two single band files, with the
+/// georeferencing of the NIR file. A pixel is NoData where either input equals
+/// `nodata` or its own file's NoData value, or where `nir + red` is zero. The
+/// output NoData value is `nodata`, else the one of the NIR file, else NaN.
+/// Other pixels are clamped to [-1, 1].
+pub fn try_ndvi<P: AsRef<Path>, Q: AsRef<Path>>(nir: P, red: Q, nodata: Option<f32>) -> Result<Raster<f32>, RasterError>{
+    let nir = Raster::<f32>::try_new(nir)?;
+    let red = Raster::<f32>::try_new(red)?;
+    normalized_difference(&nir, &red, nodata)
+}
+
+fn normalized_difference(first: &Raster<f32>, second: &Raster<f32>, nodata: Option<f32>) -> Result<Raster<f32>, RasterError>{
+    first.check_shape(second)?;
+
+    let first_nodata = first.nodata_value();
+    let second_nodata = second.nodata_value();
+    let output = nodata.or(first_nodata).unwrap_or(f32::NAN);
+    let invalid = |value: f32, own: Option<f32>| Some(value) == nodata || Some(value) == own;
+
+    let mut data = Array2::zeros(first.data.dim());
+    for (item, (&a, &b)) in zip(&mut data, zip(&first.data, &second.data)) {
+        let sum = a + b;
+        *item = if invalid(a, first_nodata) || invalid(b, second_nodata) || sum == 0.0 {
+            output
+        } else {
+            ((a - b) / sum).clamp(-1.0, 1.0)
+        };
+    }
+
+    let mut raster = first.with_data(data);
+    raster.nodata = Some(f64::from(output));
+    Ok(raster)
+}
+
+#[cfg(test)]
+mod test_indices {
+    use super::*;
+
+    #[test]
+    fn test_normalized_difference(){
+        let nir = Raster::from_vec(vec![0.8, 0.5, 0.0, 0.3, -9999.0, 0.6], 2, 3).unwrap();
+        let red = Raster::from_vec(vec![0.2, 0.5, 0.0, 0.9, 0.1, -9999.0], 2, 3).unwrap();
+        let result = normalized_difference(&nir, &red, Some(-9999.0)).unwrap();
+
+        assert_relative_eq!(result, Raster::from_vec(vec![0.6, 0.0, -9999.0, -0.5, -9999.0, -9999.0], 2, 3).unwrap(), epsilon = 1e-6);
+        assert_eq!(result.nodata, Some(-9999.0));
+    }
+
+    #[test]
+    fn test_normalized_difference_clamp(){
+        // Negative reflectances can push the ratio outside [-1, 1].
+        let nir = Raster::from_vec(vec![1.0, -0.5, 0.5, 0.5, 0.5, 0.5], 2, 3).unwrap();
+        let red = Raster::from_vec(vec![-0.5, 0.0, 0.5, 0.5, 0.5, 0.5], 2, 3).unwrap();
+        let result = normalized_difference(&nir, &red, None).unwrap();
+
+        assert_eq!(result.data[[0, 0]], 1.0);
+        assert_eq!(result.data[[0, 1]], 1.0);
+        assert_eq!(result.data[[0, 2]], 0.0);
+    }
+
+    #[test]
+    fn test_normalized_difference_own_nodata(){
+        let nir = Raster{ nodata: Some(-32768.0), ..Raster::from_vec(vec![0.8, -32768.0, 0.5, 0.5, 0.5, 0.5], 2, 3).unwrap() };
+        let red = Raster{ nodata: Some(-1.0), ..Raster::from_vec(vec![0.2, 0.2, -1.0, 0.5, 0.5, 0.5], 2, 3).unwrap() };
+        let result = normalized_difference(&nir, &red, None).unwrap();
+
+        assert_relative_eq!(result.data[[0, 0]], 0.6, epsilon = 1e-6);
+        assert_eq!(result.data[[0, 1]], -32768.0);
+        assert_eq!(result.data[[0, 2]], -32768.0);
+        assert_eq!(result.nodata, Some(-32768.0));
+    }
+
+    #[test]
+    fn test_ndvi(){
+        let result = ndvi("data/data1.asc", "data/data2.asc", None);
+        let nir = Raster::<f32>::new("data/data1.asc");
+
+        assert_eq!(result.data.dim(), (3, 4));
+        assert_eq!(result.geo_transform(), nir.geo_transform());
+        assert_eq!(result.data[[2, 3]], -32768.0);
+        assert!(result.data.iter().all(|&value| value == -32768.0 || (-1.0..=1.0).contains(&value)));
+    }
+
+    #[test]
+    #[should_panic(expected = "Could not compute the NDVI")]
+    fn test_ndvi_mismatch(){
+        ndvi("data/data1.asc", "data/data5.asc", None);
+    }
+}
@@ -0,0 +1,154 @@
+use std::convert::TryFrom;
+use ndarray::Array2;
+use super::{check_shapes, Mapping, Raster, RasterError, DEFAULT_GEOTRANSFORM};
+
+/// What `Raster::algebra_int` does when the sum of a pixel overflows `i16`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum IntMode {
+    /// Wraps around the `i16` bounds, as two's complement arithmetic.
+    Wrapping,
+    /// Clamps to `i16::MIN`, or `i16::MIN + 1` when that is NoData, or to
+    /// `i16::MAX`.
+    #[default]
+    Saturating,
+    /// Fails with `RasterError::Overflow` at the first pixel that overflows.
+    Checked,
+}
+
+impl IntMode {
+    /// Brings the exact sum of a pixel into `i16`, keeping `fill` for NoData,
+    /// or None if `Checked` overflows.
+    fn finish(self, sum: i64, fill: i16) -> Option<i16>{
+        match self {
+            IntMode::Wrapping => Some(sum as i16),
+            // Saturating down to the default NoData value would make it NoData.
+            IntMode::Saturating if fill == i16::MIN => Some(sum.clamp(i64::from(i16::MIN) + 1, i64::from(i16::MAX)) as i16),
+            IntMode::Saturating => Some(sum.clamp(i64::from(i16::MIN), i64::from(i16::MAX)) as i16),
+            IntMode::Checked => i16::try_from(sum).ok(),
+        }
+    }
+}
+
+impl Raster<i16> {
+    /// Weighted sum of integer maps with integer weights, in the given order.
+    /// Each pixel is summed exactly in `i64` and `mode` handles only the final
+    /// sums that overflow `i16`, so the order of the maps does not matter. A
+    /// pixel equal to its map's NoData value makes the output pixel NoData,
+    /// which is the value of the first map, or `i16::MIN` without one. When it
+    /// is `i16::MIN`, the sums saturate at `i16::MIN + 1` instead, so that they
+    /// stay apart from NoData. A wrapped or checked sum equal to the NoData
+    /// value still reads as NoData. The result takes the georeferencing of the first map.
+    ///
+    /// With `IntMode::Checked` the error is the first overflowing pixel in
+    /// row-major order.
+    pub fn algebra_int(maps: Vec<(String, i16)>, mode: IntMode) -> Result<Raster<i16>, RasterError>{
+        check_shapes(maps.iter().map(|map| &map.0))?;
+
+        let first = match maps.first() {
+            Some(map) => Raster::<i16>::try_new(&map.0)?,
+            // Zero items.
+            None => return Ok(Raster::<i16>{
+                data: array![[]],
+                nodata: None,
+                geotransform: DEFAULT_GEOTRANSFORM,
+                projection: String::new()
+            }),
+        };
+        let fill = first.nodata.map_or(i16::MIN, |nodata| nodata as i16);
+        let mut sums: Array2<i64> = Array2::zeros(first.data.dim());
+        let mut missing: Array2<bool> = Array2::default(first.data.dim());
+
+        let mut add = |raster: &Raster<i16>, weight: i16| {
+            for ((sum, flag), &value) in sums.iter_mut().zip(missing.iter_mut()).zip(raster.data.iter()) {
+                if Some(f64::from(value)) == raster.nodata {
+                    *flag = true;
+                } else {
+                    *sum += i64::from(value) * i64::from(weight);
+                }
+            }
+        };
+
+        add(&first, maps[0].1);
+        for map in maps.iter().skip(1) {
+            add(&Raster::<i16>::try_new(&map.0)?, map.1);
+        }
+
+        let mut data = Array2::zeros(first.data.dim());
+        for (((row, col), item), (&sum, &flag)) in data.indexed_iter_mut().zip(sums.iter().zip(missing.iter())) {
+            *item = if flag {
+                fill
+            } else {
+                mode.finish(sum, fill).ok_or(RasterError::Overflow{ row, col })?
+            };
+        }
+
+        Ok(Raster::<i16>{
+            data,
+            nodata: Some(f64::from(fill)),
+            geotransform: first.geotransform,
+            projection: first.projection
+        })
+    }
+}
+
+#[cfg(test)]
+mod test_integer {
+    use super::*;
+
+    fn overflowing() -> Vec<(String, i16)>{
+        vec![("data/classes1.asc".to_string(), 10000), ("data/classes2.asc".to_string(), 1)]
+    }
+
+    #[test]
+    fn test_algebra_int(){
+        let maps = vec![("data/classes1.asc".to_string(), 2), ("data/classes2.asc".to_string(), 1)];
+        let combination = Raster::<i16>::algebra_int(maps, IntMode::Checked).unwrap();
+
+        assert_eq!(combination.data, array![[12, 24, 36, 48], [14, 14, 36, -32768], [60, 60, -32768, -32768]]);
+        assert_eq!(combination.nodata, Some(-32768.0));
+    }
+
+    #[test]
+    fn test_algebra_int_saturating(){
+        let combination = Raster::<i16>::algebra_int(overflowing(), IntMode::default()).unwrap();
+
+        assert_eq!(combination.data.row(0), array![10010, 20020, 30030, i16::MAX]);
+        assert_eq!(combination.data.row(2), array![i16::MAX, i16::MAX, -32768, -32768]);
+
+        // Saturating downwards stops short of the NoData value.
+        let maps = vec![("data/classes1.asc".to_string(), -10000)];
+        let combination = Raster::<i16>::algebra_int(maps, IntMode::Saturating).unwrap();
+        assert_eq!(combination.data.row(0), array![-10000, -20000, -30000, i16::MIN + 1]);
+    }
+
+    #[test]
+    fn test_algebra_int_wrapping(){
+        let combination = Raster::<i16>::algebra_int(overflowing(), IntMode::Wrapping).unwrap();
+
+        // 40040 wraps to 40040 - 65536.
+        assert_eq!(combination.data.row(0), array![10010, 20020, 30030, -25496]);
+    }
+
+    #[test]
+    fn test_algebra_int_order(){
+        // The first two maps overflow on their own, but the sum fits.
+        let maps = vec![
+            ("data/classes1.asc".to_string(), 10000),
+            ("data/classes1.asc".to_string(), 10000),
+            ("data/classes1.asc".to_string(), -20000),
+        ];
+
+        for &mode in &[IntMode::Checked, IntMode::Saturating, IntMode::Wrapping] {
+            let combination = Raster::<i16>::algebra_int(maps.clone(), mode).unwrap();
+            assert_eq!(combination.data, array![[0, 0, 0, 0], [0, 0, 0, -32768], [0, 0, -32768, -32768]]);
+        }
+    }
+
+    #[test]
+    fn test_algebra_int_checked(){
+        match Raster::<i16>::algebra_int(overflowing(), IntMode::Checked) {
+            Err(RasterError::Overflow{ row, col }) => assert_eq!((row, col), (0, 3)),
+            other => panic!("Expected an overflow error, got {:?}", other),
+        }
+    }
+}
@@ -1,214 +1,1868 @@
-#[macro_use]
-extern crate ndarray;
-
-#[macro_use]
-extern crate approx;
-
-extern crate gdal;
-extern crate itertools;
-
-use std::collections::HashMap;
-use std::path::Path;
-use gdal::raster::Dataset;
-use ndarray::Array2;
-use itertools::zip;
-pub use approx::{AbsDiffEq, RelativeEq, UlpsEq};
-
-#[derive(Debug, PartialEq)]
-pub struct Raster<T>{
-    pub data: Array2<T>
-}
-
-pub trait Mapping<TypeData>{
-    fn get_data(filename: &String) -> TypeData;
-    fn new(filename: String) -> Self;
-    fn algebra(maps: HashMap<String, f32>) -> Self;
-}
-
-impl Mapping<Array2<f32>> for Raster<f32>
-{
-    fn get_data(filename: &String) -> Array2<f32>{
-        let path = Path::new(filename);
-        let dataset = Dataset::open(path).unwrap();
-        let shape = dataset.size();
-        let buffer = dataset.read_full_raster_as::<f32>(1).unwrap();
-        let (cols, rows) = shape;
-        Array2::from_shape_vec((rows, cols), buffer.data).unwrap()
-    }
-    
-    fn new(filename: String) -> Self{
-        Raster::<f32>{
-            data: Self::get_data(&filename)
-        }
-    }
-    
-    fn algebra(maps: HashMap<String, f32>) -> Self{
-        let mut data: Array2<f32>;
-        let mut weight: f32;
-        let mut result: Array2<f32> = array![[]];
-
-        let first = maps.iter().next();  
-        match first {
-            // Result receives the first data
-            Some(map) => {
-                data = Self::get_data(map.0);
-                weight = *map.1;
-                result = weight * data;
-            }
-            // Zero items.
-            None => println!("No maps.")
-        }
-        
-        for map in maps.iter().skip(1){
-            data = Self::get_data(map.0);
-            weight = *map.1;
-            result = result + weight * data;
-        }
-        
-        Raster::<f32>{
-            data: result
-        }
-    }
-}
-
-impl<T: AbsDiffEq> AbsDiffEq for Raster<T>
-where
-    T::Epsilon: Copy,
-{
-    type Epsilon = T::Epsilon;
-    fn default_epsilon() -> T::Epsilon {
-        T::default_epsilon()
-    }
-    
-    fn abs_diff_eq(&self, other: &Self, epsilon: T::Epsilon) -> bool {
-        for (item1, item2) in zip(&self.data, &other.data){
-            if !T::abs_diff_eq(item1, item2, epsilon){
-                return false;
-            }
-        }
-        true
-    }
-}
-
-impl<T: RelativeEq> RelativeEq for Raster<T>
-where
-    T::Epsilon: Copy,
-{
-    fn default_max_relative() -> T::Epsilon {
-        T::default_max_relative()
-    }
-    
-    fn relative_eq(&self, other: &Self, epsilon: T::Epsilon, max_relative: T::Epsilon) -> bool {
-        
-        for (item1, item2) in zip(&self.data, &other.data){
-            if !T::relative_eq(item1, item2, epsilon, max_relative){
-                return false;
-            }
-        }
-        true
-    }
-}
-
-impl<T: UlpsEq> UlpsEq for Raster<T>
-where
-    T::Epsilon: Copy,
-{
-    fn default_max_ulps() -> u32 {
-        T::default_max_ulps()
-    }
-    
-    fn ulps_eq(&self, other: &Self, epsilon: T::Epsilon, max_ulps: u32) -> bool {
-        for (item1, item2) in zip(&self.data, &other.data){
-            if !T::ulps_eq(item1, item2, epsilon, max_ulps){
-                return false;
-            }
-        }
-        true
-    }
-}
-
-#[cfg(test)]
-mod test_approx {
-    use super::*;
-
-    #[test]
-    fn test_abs_diff_eq(){
-        let raster1 = Raster::new("data/data1.asc".to_string());
-        let raster2 = Raster::<f32>{
-            data: Array2::from_shape_vec((3, 4), vec![0.388889, 0.513889, 0.638889, 0.805556, 0.444447, 0.583333, 0.680556, -32768.0, 0.625, 0.625, -32768.0, -32768.0]).unwrap()
-        };
-
-        assert_abs_diff_eq!(raster1, raster2);
-    }
-
-    #[test]
-    fn test_relative_eq(){
-        let raster1 = Raster::new("data/data1.asc".to_string());
-        let raster2 = Raster::<f32>{
-            data: Array2::from_shape_vec((3, 4), vec![0.388889, 0.513889, 0.638889, 0.805556, 0.444447, 0.583333, 0.680556, -32768.0, 0.625, 0.625, -32768.0, -32768.0]).unwrap()
-        };
-
-        assert_relative_eq!(raster1, raster2);
-    }
-
-    #[test]
-    fn test_ulps_eq(){
-        let raster1 = Raster::new("data/data1.asc".to_string());
-        let raster2 = Raster::<f32>{
-            data: Array2::from_shape_vec((3, 4), vec![0.388889, 0.513889, 0.638889, 0.805556, 0.444447, 0.583333, 0.680556, -32768.0, 0.625, 0.625, -32768.0, -32768.0]).unwrap()
-        };
-        
-        assert_ulps_eq!(raster1, raster2, max_ulps = 6);
-    }
-}
-
-#[cfg(test)]
-mod test_algebra {
-    use super::*;
-
-    #[test]
-    fn test_new_data(){
-        let weight1: f32 = 0.4;
-        let weight2: f32 = 0.2;
-        let weight3: f32 = 0.2;
-        let weight4: f32 = 0.2;
-        
-        let raster1 = Raster::new("data/data1.asc".to_string());
-        let raster2 = Raster::new("data/data2.asc".to_string());
-        let raster3 = Raster::new("data/data3.asc".to_string());
-        let raster4 = Raster::new("data/data4.asc".to_string());
-        let result = Raster::new("data/result.asc".to_string());
-
-        let combination = Raster::<f32>{
-            data: weight1 * raster1.data + weight2 * raster2.data + weight3 * raster3.data + weight4 * raster4.data
-        };
-
-        assert_relative_eq!(combination, result, epsilon = 1e-5f32);
-
-    }
-
-    #[test]
-    fn test_new_algebra(){
-        let mut maps: HashMap<String, f32> = HashMap::new();
-
-        maps.insert("data/data1.asc".to_string(), 0.4);
-        maps.insert("data/data2.asc".to_string(), 0.2);
-        maps.insert("data/data3.asc".to_string(), 0.2);
-        maps.insert("data/data4.asc".to_string(), 0.2);
-        
-        let result = Raster::new("data/result.asc".to_string());
-        let combination = Raster::algebra(maps);
-
-        assert_relative_eq!(combination, result, epsilon = 1e-5f32);
-    }
-
-    #[test]
-    fn test_new_algebra_zero(){
-        let maps: HashMap<String, f32> = HashMap::new();
-        let empty: Array2<f32> = array![[]];
-        let result = Raster::<f32>{data: empty};
-        let combination = Raster::algebra(maps);
-
-        assert_eq!(combination, result);
-    }
-}
+#[macro_use]
+extern crate ndarray;
+
+#[cfg_attr(test, macro_use)]
+extern crate approx;
+
+extern crate gdal;
+extern crate gdal_sys;
+extern crate itertools;
+extern crate ordered_float;
+extern crate rayon;
+
+#[cfg(feature = "serde")]
+#[macro_use]
+extern crate serde;
+#[cfg(all(test, feature = "serde"))]
+extern crate serde_json;
+
+mod asc;
+mod blocked;
+mod classify;
+mod color;
+mod contour;
+mod distance;
+pub mod error;
+mod expr;
+mod ffi;
+mod focal;
+mod georef;
+mod hydrology;
+mod indices;
+mod integer;
+mod mask;
+mod metadata;
+mod mosaic;
+mod ops;
+mod orient;
+mod overlay;
+mod polygon;
+mod resample;
+mod rescale;
+mod stack;
+mod stats;
+mod terrain;
+mod warp;
+
+use std::collections::HashMap;
+use std::fmt;
+use std::fs;
+use std::ops::Sub;
+use std::path::Path;
+use std::str::FromStr;
+use gdal::raster::{Buffer, Dataset, Driver};
+use ndarray::{Array2, ArrayView2, ArrayViewMut2};
+use itertools::zip;
+use rayon::prelude::*;
+pub use approx::{AbsDiffEq, RelativeEq, UlpsEq};
+pub use asc::asc_nodata;
+pub use color::ColorMap;
+pub use error::RasterError;
+pub use georef::Interp;
+pub use indices::{ndvi, try_ndvi};
+pub use integer::IntMode;
+pub use metadata::{band_info, metadata, metadata_item, try_metadata, try_metadata_item, BandInfo};
+pub use ordered_float::OrderedFloat;
+pub use resample::AggMethod;
+pub use stack::RasterStack;
+pub use stats::{DiffStats, RasterStats};
+pub use warp::ResampleMethod;
+
+/// Geotransform GDAL reports for datasets without georeferencing.
+pub const DEFAULT_GEOTRANSFORM: [f64; 6] = [0.0, 1.0, 0.0, 0.0, 0.0, 1.0];
+
+/// A single raster band with its NoData value, if the source declares one,
+/// and its georeferencing.
+///
+/// `PartialEq` compares every field, while the approx comparisons only look
+/// at the data. Both follow IEEE semantics, where a NaN pixel is not equal to
+/// anything, see `nan_equal` for rasters using NaN as NoData.
+///
+/// With the `serde` feature it can be serialized with all its fields.
+#[derive(Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Raster<T>{
+    pub data: Array2<T>,
+    pub nodata: Option<f64>,
+    pub geotransform: [f64; 6],
+    pub projection: String
+}
+
+impl<T> Raster<T> {
+    /// Wraps row-major `data` of `rows` by `cols` pixels without NoData value
+    /// nor georeferencing, failing if its length is not `rows * cols`.
+    pub fn from_vec(data: Vec<T>, rows: usize, cols: usize) -> Result<Raster<T>, RasterError>{
+        let got = data.len();
+        Array2::from_shape_vec((rows, cols), data)
+            .map(Raster::from)
+            .map_err(|_| RasterError::Shape{ expected: (rows, cols), got })
+    }
+
+    /// Dimensions as `(rows, cols)`.
+    pub fn shape(&self) -> (usize, usize){
+        self.data.dim()
+    }
+
+    /// Number of pixels.
+    pub fn len(&self) -> usize{
+        self.data.len()
+    }
+
+    pub fn is_empty(&self) -> bool{
+        self.data.is_empty()
+    }
+
+    pub fn geo_transform(&self) -> [f64; 6]{
+        self.geotransform
+    }
+
+    /// The projection as WKT, empty when the source has none.
+    pub fn projection(&self) -> &str{
+        &self.projection
+    }
+
+    /// Wraps `data` with the NoData value and georeferencing of this raster.
+    pub(crate) fn with_data<U>(&self, data: Array2<U>) -> Raster<U>{
+        Raster::<U>{
+            data,
+            nodata: self.nodata,
+            geotransform: self.geotransform,
+            projection: self.projection.clone()
+        }
+    }
+
+    /// Wraps the `data` of a mask or of region labels with the georeferencing
+    /// of this raster and no NoData value, as the NoData pixels are already
+    /// the background 0.
+    pub(crate) fn with_mask<U>(&self, data: Array2<U>) -> Raster<U>{
+        let mut raster = self.with_data(data);
+        raster.nodata = None;
+        raster
+    }
+
+    pub(crate) fn check_shape<U>(&self, other: &Raster<U>) -> Result<(), RasterError>{
+        if self.data.dim() != other.data.dim() {
+            return Err(RasterError::DimensionMismatch{ expected: self.data.dim(), got: other.data.dim() });
+        }
+        Ok(())
+    }
+}
+
+/// Wraps the data without NoData value nor georeferencing.
+impl<T> From<Array2<T>> for Raster<T> {
+    fn from(data: Array2<T>) -> Raster<T>{
+        Raster::<T>{
+            data,
+            nodata: None,
+            geotransform: DEFAULT_GEOTRANSFORM,
+            projection: String::new()
+        }
+    }
+}
+
+/// Keeps the data, dropping the metadata.
+impl<T> From<Raster<T>> for Array2<T> {
+    fn from(raster: Raster<T>) -> Array2<T>{
+        raster.data
+    }
+}
+
+fn open_dataset<P: AsRef<Path>>(filename: P) -> Result<Dataset, RasterError>{
+    let path = filename.as_ref();
+    Dataset::open(path).map_err(|error| RasterError::Open(path.to_path_buf(), Box::new(error)))
+}
+
+/// The path as the UTF-8 string GDAL creates files from.
+fn path_str(path: &Path) -> Result<&str, RasterError>{
+    path.to_str().ok_or_else(|| RasterError::Path(path.to_path_buf()))
+}
+
+fn check_band(dataset: &Dataset, band: isize) -> Result<(), RasterError>{
+    let count = dataset.count();
+    if band < 1 || band > count {
+        return Err(RasterError::Band{ band, count });
+    }
+    Ok(())
+}
+
+fn band_nodata(dataset: &Dataset, band: isize) -> Result<Option<f64>, RasterError>{
+    check_band(dataset, band)?;
+    Ok(dataset.rasterband(band)?.no_data_value())
+}
+
+/// Checks that the window, in GDAL `(x, y)` order, lies inside the dataset.
+fn check_window(dataset: &Dataset, offset: (isize, isize), size: (usize, usize)) -> Result<(), RasterError>{
+    let raster = dataset.size();
+    let inside = |start: isize, length: usize, total: usize| {
+        start >= 0 && (start as usize).checked_add(length).is_some_and(|end| end <= total)
+    };
+    if !inside(offset.0, size.0, raster.0) || !inside(offset.1, size.1, raster.1) {
+        return Err(RasterError::Window{ offset, size, raster });
+    }
+    Ok(())
+}
+
+/// Moves the origin of `geotransform` to the pixel at `offset`, in GDAL `(x, y)` order.
+fn window_geotransform(geotransform: [f64; 6], offset: (isize, isize)) -> [f64; 6]{
+    let (x, y) = (offset.0 as f64, offset.1 as f64);
+    let mut moved = geotransform;
+    moved[0] += x * geotransform[1] + y * geotransform[2];
+    moved[3] += x * geotransform[4] + y * geotransform[5];
+    moved
+}
+
+/// Reads the `path,weight` lines of a CSV file after its header row, skipping
+/// blank lines.
+fn read_weights<P: AsRef<Path>, W: FromStr>(config: P) -> Result<Vec<(String, W)>, RasterError>{
+    let config = config.as_ref();
+    let text = fs::read_to_string(config).map_err(|error| RasterError::Io(config.to_path_buf(), error))?;
+    let malformed = |line: usize, message: String| RasterError::Config{ file: config.to_path_buf(), line, message };
+
+    let mut maps = Vec::new();
+    for (index, line) in text.lines().enumerate().skip(1) {
+        if line.trim().is_empty() {
+            continue;
+        }
+        let fields: Vec<&str> = line.split(',').map(str::trim).collect();
+        if fields.len() != 2 || fields[0].is_empty() {
+            return Err(malformed(index + 1, format!("expected path,weight, got {:?}", line)));
+        }
+        let weight = fields[1].parse()
+            .map_err(|_| malformed(index + 1, format!("invalid weight {:?}", fields[1])))?;
+        maps.push((fields[0].to_string(), weight));
+    }
+    Ok(maps)
+}
+
+/// Checks that every file has the dimensions of the first one.
+fn check_shapes<I, S>(filenames: I) -> Result<(), RasterError>
+where
+    I: IntoIterator<Item = S>,
+    S: AsRef<Path>,
+{
+    let mut expected: Option<(usize, usize)> = None;
+
+    for filename in filenames {
+        let (cols, rows) = open_dataset(&filename)?.size();
+        match expected {
+            None => expected = Some((rows, cols)),
+            Some(expected) if expected != (rows, cols) => {
+                return Err(RasterError::ShapeMismatch{ file: filename.as_ref().display().to_string(), expected, got: (rows, cols) });
+            }
+            Some(_) => ()
+        }
+    }
+    Ok(())
+}
+
+pub trait Mapping<TypeData, Weight = f32>: Sized {
+    fn get_data<P: AsRef<Path>>(filename: P) -> TypeData;
+    fn try_get_data<P: AsRef<Path>>(filename: P) -> Result<TypeData, RasterError>;
+    fn get_band<P: AsRef<Path>>(filename: P, band: isize) -> TypeData;
+    /// Reads the 1-based `band`, checking it against the dataset band count.
+    fn try_get_band<P: AsRef<Path>>(filename: P, band: isize) -> Result<TypeData, RasterError>;
+    fn new<P: AsRef<Path>>(filename: P) -> Self;
+    fn try_new<P: AsRef<Path>>(filename: P) -> Result<Self, RasterError>;
+    fn new_band<P: AsRef<Path>>(filename: P, band: isize) -> Self;
+    fn try_new_band<P: AsRef<Path>>(filename: P, band: isize) -> Result<Self, RasterError>;
+    /// Reads only the window of band 1 at `offset` with `size`, both in GDAL
+    /// `(x, y)` order, that is `(col, row)` and `(cols, rows)`.
+    fn new_window<P: AsRef<Path>>(filename: P, offset: (isize, isize), size: (usize, usize)) -> Result<Self, RasterError>;
+    fn nodata<P: AsRef<Path>>(filename: P) -> Option<f64>;
+    /// Reads the NoData value of band 1.
+    fn try_nodata<P: AsRef<Path>>(filename: P) -> Result<Option<f64>, RasterError>;
+    fn algebra(maps: HashMap<String, Weight>) -> Self;
+    fn try_algebra(maps: HashMap<String, Weight>) -> Result<Self, RasterError>;
+    fn algebra_ordered(maps: Vec<(String, Weight)>) -> Self;
+    fn try_algebra_ordered(maps: Vec<(String, Weight)>) -> Result<Self, RasterError>;
+    fn algebra_nodata(maps: HashMap<String, Weight>, nodata: Weight) -> Self;
+    /// Weighted sum where a pixel equal to `nodata` in any map makes the
+    /// output pixel `nodata`, failing if a map cannot be read.
+    fn try_algebra_nodata(maps: HashMap<String, Weight>, nodata: Weight) -> Result<Self, RasterError>;
+    fn algebra_parallel(maps: HashMap<String, Weight>) -> Self;
+    fn weighted_average(maps: HashMap<String, Weight>) -> Self;
+    /// Weighted sum with the weights divided by their total.
+    fn try_weighted_average(maps: HashMap<String, Weight>) -> Result<Self, RasterError>;
+    /// Weighted sum of the maps, reading and weighting them on the rayon thread pool.
+    fn try_algebra_parallel(maps: HashMap<String, Weight>) -> Result<Self, RasterError>;
+    /// Ordered weighted sum of the maps listed in a `path,weight` CSV file
+    /// with a header row.
+    fn algebra_from_file<P: AsRef<Path>>(config: P) -> Result<Self, RasterError>;
+    /// Weighted sum of the maps, in filename order, after checking that every
+    /// weight is finite and not negative and that they sum to 1 within
+    /// `tolerance`, which fails with a NaN `tolerance`.
+    fn algebra_validated(maps: HashMap<String, Weight>, tolerance: Weight) -> Result<Self, RasterError>;
+    /// Weighted sum of the maps, in filename order, where each path maps to
+    /// its `(weight, nodata)` and the output NoData value is `nodata`.
+    fn algebra_with_nodata(maps: HashMap<String, (Weight, Weight)>, nodata: Weight) -> Result<Self, RasterError>;
+}
+
+/// Expands to the `Mapping` methods reading files, which do not depend on the
+/// weight type.
+macro_rules! read_methods {
+    ($t:ty) => {
+        fn get_data<P: AsRef<Path>>(filename: P) -> Array2<$t>{
+            let filename = filename.as_ref();
+            Self::try_get_data(filename)
+                .unwrap_or_else(|error| panic!("Could not read the raster {}: {}", filename.display(), error))
+        }
+
+        fn try_get_data<P: AsRef<Path>>(filename: P) -> Result<Array2<$t>, RasterError>{
+            Self::try_get_band(filename, 1)
+        }
+
+        fn get_band<P: AsRef<Path>>(filename: P, band: isize) -> Array2<$t>{
+            let filename = filename.as_ref();
+            Self::try_get_band(filename, band)
+                .unwrap_or_else(|error| panic!("Could not read the raster {}: {}", filename.display(), error))
+        }
+
+        fn try_get_band<P: AsRef<Path>>(filename: P, band: isize) -> Result<Array2<$t>, RasterError>{
+            let dataset = open_dataset(filename)?;
+            check_band(&dataset, band)?;
+            let shape = dataset.size();
+            let buffer = dataset.read_full_raster_as::<$t>(band)?;
+            let (cols, rows) = shape;
+            let got = buffer.data.len();
+            Array2::from_shape_vec((rows, cols), buffer.data)
+                .map_err(|_| RasterError::Shape{ expected: (rows, cols), got })
+        }
+
+        fn new<P: AsRef<Path>>(filename: P) -> Self{
+            Self::try_new(filename).unwrap_or_else(|error| panic!("Could not create the raster: {}", error))
+        }
+
+        fn try_new<P: AsRef<Path>>(filename: P) -> Result<Self, RasterError>{
+            Self::try_new_band(filename, 1)
+        }
+
+        fn new_band<P: AsRef<Path>>(filename: P, band: isize) -> Self{
+            Self::try_new_band(filename, band).unwrap_or_else(|error| panic!("Could not create the raster: {}", error))
+        }
+
+        fn try_new_band<P: AsRef<Path>>(filename: P, band: isize) -> Result<Self, RasterError>{
+            let filename = filename.as_ref();
+            let dataset = open_dataset(filename)?;
+            Ok(Raster::<$t>{
+                data: Self::try_get_band(filename, band)?,
+                nodata: band_nodata(&dataset, band)?,
+                geotransform: dataset.geo_transform().unwrap_or(DEFAULT_GEOTRANSFORM),
+                projection: dataset.projection()
+            })
+        }
+
+        /// The origin of the geotransform is moved to the window corner.
+        fn new_window<P: AsRef<Path>>(filename: P, offset: (isize, isize), size: (usize, usize)) -> Result<Self, RasterError>{
+            let dataset = open_dataset(filename)?;
+            check_window(&dataset, offset, size)?;
+            let buffer = dataset.read_raster_as::<$t>(1, offset, size, size)?;
+            let (cols, rows) = size;
+            let got = buffer.data.len();
+            let data = Array2::from_shape_vec((rows, cols), buffer.data)
+                .map_err(|_| RasterError::Shape{ expected: (rows, cols), got })?;
+            let geotransform = dataset.geo_transform().unwrap_or(DEFAULT_GEOTRANSFORM);
+
+            Ok(Raster::<$t>{
+                data,
+                nodata: band_nodata(&dataset, 1)?,
+                geotransform: window_geotransform(geotransform, offset),
+                projection: dataset.projection()
+            })
+        }
+
+        fn nodata<P: AsRef<Path>>(filename: P) -> Option<f64>{
+            let filename = filename.as_ref();
+            Self::try_nodata(filename)
+                .unwrap_or_else(|error| panic!("Could not read the raster {}: {}", filename.display(), error))
+        }
+
+        fn try_nodata<P: AsRef<Path>>(filename: P) -> Result<Option<f64>, RasterError>{
+            band_nodata(&open_dataset(filename)?, 1)
+        }
+    }
+}
+/// Implements `Mapping` for a pixel type GDAL can read directly, using the
+/// same type for the weights.
+macro_rules! impl_mapping {
+    ($t:ty) => {
+        impl Mapping<Array2<$t>, $t> for Raster<$t>
+        {
+            read_methods!($t);
+
+            fn algebra(maps: HashMap<String, $t>) -> Self{
+                Self::try_algebra(maps).unwrap_or_else(|error| panic!("Could not combine the rasters: {}", error))
+            }
+
+            /// Weighted sum of the maps, see `try_algebra_ordered`. The HashMap
+            /// iteration order is arbitrary, so the map whose metadata is inherited
+            /// and the summation order can change between runs.
+            fn try_algebra(maps: HashMap<String, $t>) -> Result<Self, RasterError>{
+                Self::try_algebra_ordered(maps.into_iter().collect())
+            }
+
+            fn algebra_ordered(maps: Vec<(String, $t)>) -> Self{
+                Self::try_algebra_ordered(maps).unwrap_or_else(|error| panic!("Could not combine the rasters: {}", error))
+            }
+
+            /// Weighted sum of the maps in the given order, checking first that they
+            /// all have the same dimensions. The result takes the NoData value and
+            /// the georeferencing of the first map.
+            fn try_algebra_ordered(maps: Vec<(String, $t)>) -> Result<Self, RasterError>{
+                check_shapes(maps.iter().map(|map| &map.0))?;
+
+                let mut data: Array2<$t>;
+                let mut weight: $t;
+                let mut result = Raster::<$t>{
+                    data: array![[]],
+                    nodata: None,
+                    geotransform: DEFAULT_GEOTRANSFORM,
+                    projection: String::new()
+                };
+
+                // Result receives the first data, and stays empty with zero items.
+                if let Some(map) = maps.first() {
+                    let raster = Self::try_new(map.0.clone())?;
+                    weight = map.1;
+                    result = raster.with_data(weight * &raster.data);
+                }
+
+                for map in maps.iter().skip(1){
+                    data = Self::try_get_data(&map.0)?;
+                    weight = map.1;
+                    result.data = result.data + weight * data;
+                }
+
+                Ok(result)
+            }
+
+            fn algebra_parallel(maps: HashMap<String, $t>) -> Self{
+                Self::try_algebra_parallel(maps).unwrap_or_else(|error| panic!("Could not combine the rasters: {}", error))
+            }
+
+            /// The summation order depends on the scheduling, so the result only
+            /// matches `algebra` within floating point tolerance. The metadata is
+            /// taken from the first map iterated, as in `algebra`.
+            fn try_algebra_parallel(maps: HashMap<String, $t>) -> Result<Self, RasterError>{
+                let maps: Vec<(String, $t)> = maps.into_iter().collect();
+                check_shapes(maps.iter().map(|map| &map.0))?;
+
+                let first = match maps.first() {
+                    Some(map) => open_dataset(&map.0)?,
+                    // Zero items.
+                    None => {
+                        return Ok(Raster::<$t>{
+                            data: array![[]],
+                            nodata: None,
+                            geotransform: DEFAULT_GEOTRANSFORM,
+                            projection: String::new()
+                        });
+                    }
+                };
+
+                let data = maps.par_iter()
+                    .map(|map| Self::try_get_data(&map.0).map(|data| map.1 * data))
+                    .try_reduce_with(|sum, data| Ok(sum + data))
+                    .expect("there is at least one map")?;
+
+                Ok(Raster::<$t>{
+                    data,
+                    nodata: band_nodata(&first, 1)?,
+                    geotransform: first.geo_transform().unwrap_or(DEFAULT_GEOTRANSFORM),
+                    projection: first.projection()
+                })
+            }
+
+            fn weighted_average(maps: HashMap<String, $t>) -> Self{
+                Self::try_weighted_average(maps).unwrap_or_else(|error| panic!("Could not combine the rasters: {}", error))
+            }
+
+            fn algebra_from_file<P: AsRef<Path>>(config: P) -> Result<Self, RasterError>{
+                Self::try_algebra_ordered(read_weights(config)?)
+            }
+
+            fn algebra_validated(maps: HashMap<String, $t>, tolerance: $t) -> Result<Self, RasterError>{
+                let mut maps: Vec<(String, $t)> = maps.into_iter().collect();
+                maps.sort_by(|a, b| a.0.cmp(&b.0));
+
+                if let Some(map) = maps.iter().find(|map| !map.1.is_finite() || map.1 < 0.0) {
+                    return Err(RasterError::InvalidWeight{ file: map.0.clone(), weight: f64::from(map.1) });
+                }
+                let sum: $t = maps.iter().map(|map| map.1).sum();
+                if tolerance.is_nan() || (sum - 1.0).abs() > tolerance {
+                    return Err(RasterError::WeightSum{ sum: f64::from(sum), tolerance: f64::from(tolerance) });
+                }
+                Self::try_algebra_ordered(maps)
+            }
+
+            /// A pixel equal to the NoData value of its own map, or NaN when that
+            /// value is NaN, makes the output pixel `nodata`. The result takes the
+            /// georeferencing of the first map in filename order.
+            fn algebra_with_nodata(maps: HashMap<String, ($t, $t)>, nodata: $t) -> Result<Self, RasterError>{
+                let mut maps: Vec<(String, ($t, $t))> = maps.into_iter().collect();
+                maps.sort_by(|a, b| a.0.cmp(&b.0));
+                check_shapes(maps.iter().map(|map| &map.0))?;
+
+                let first = match maps.first() {
+                    Some(map) => Self::try_new(&map.0)?,
+                    None => return Self::try_algebra_ordered(Vec::new()),
+                };
+                let mut data: Array2<$t> = Array2::zeros(first.data.dim());
+                let mut missing: Array2<bool> = Array2::default(first.data.dim());
+
+                for (index, map) in maps.iter().enumerate() {
+                    let (weight, sentinel) = map.1;
+                    let read;
+                    let values = if index == 0 {
+                        &first.data
+                    } else {
+                        read = Self::try_get_data(&map.0)?;
+                        &read
+                    };
+
+                    for ((item, flag), &value) in zip(zip(&mut data, &mut missing), values) {
+                        if value == sentinel || (sentinel.is_nan() && value.is_nan()) {
+                            *flag = true;
+                        } else {
+                            *item += weight * value;
+                        }
+                    }
+                }
+                for (item, &flag) in zip(&mut data, &missing) {
+                    if flag {
+                        *item = nodata;
+                    }
+                }
+
+                let mut result = first.with_data(data);
+                result.nodata = Some(f64::from(nodata));
+                Ok(result)
+            }
+
+            /// A zero total weight gives the same empty raster as zero maps.
+            fn try_weighted_average(maps: HashMap<String, $t>) -> Result<Self, RasterError>{
+                let total: $t = maps.values().sum();
+                if total == 0.0 {
+                    return Self::try_algebra_ordered(Vec::new());
+                }
+                Self::try_algebra(maps.into_iter().map(|(filename, weight)| (filename, weight / total)).collect())
+            }
+
+            fn algebra_nodata(maps: HashMap<String, $t>, nodata: $t) -> Self{
+                Self::try_algebra_nodata(maps, nodata).unwrap_or_else(|error| panic!("Could not combine the rasters: {}", error))
+            }
+
+            /// Whatever its weight, a NoData pixel of any map makes the output
+            /// pixel `nodata`. The georeferencing comes from the first map
+            /// iterated, as in `algebra`. Zero maps give an empty raster.
+            fn try_algebra_nodata(maps: HashMap<String, $t>, nodata: $t) -> Result<Self, RasterError>{
+                check_shapes(maps.keys())?;
+
+                let mut result: Array2<$t> = array![[]];
+                let mut geotransform = DEFAULT_GEOTRANSFORM;
+                let mut projection = String::new();
+                let mut missing: Array2<bool> = Array2::default((1, 0));
+
+                for (index, map) in maps.iter().enumerate(){
+                    let data = Self::try_get_data(map.0)?;
+                    let weight = *map.1;
+
+                    if index == 0 {
+                        // Result receives the first data and georeferencing
+                        let dataset = open_dataset(map.0)?;
+                        geotransform = dataset.geo_transform().unwrap_or(DEFAULT_GEOTRANSFORM);
+                        projection = dataset.projection();
+                        missing = data.mapv(|value| value == nodata);
+                        result = weight * data;
+                    }
+                    else {
+                        for (flag, value) in zip(&mut missing, &data){
+                            *flag = *flag || *value == nodata;
+                        }
+                        result = result + weight * data;
+                    }
+                }
+
+                for (item, flag) in zip(&mut result, &missing){
+                    if *flag {
+                        *item = nodata;
+                    }
+                }
+
+                Ok(Raster::<$t>{
+                    data: result,
+                    nodata: Some(f64::from(nodata)),
+                    geotransform,
+                    projection
+                })
+            }
+        }
+    }
+}
+
+impl_mapping!(f32);
+impl_mapping!(f64);
+
+/// Rounds a weighted sum to the nearest integer, saturating at the `i16`
+/// bounds.
+fn round_i16(raster: Raster<f32>) -> Raster<i16>{
+    raster.with_data(raster.data.mapv(|value| value.round() as i16))
+}
+
+/// Integer rasters, such as classified maps, read as `i16`. The algebra reads
+/// the maps as `f32`, sums them with `f32` weights and rounds the result, see
+/// `round_i16`, which saturates as the default `IntMode`. `Raster::algebra_int`
+/// sums in integers with a choice of overflow behavior.
+impl Mapping<Array2<i16>> for Raster<i16> {
+    read_methods!(i16);
+
+    fn algebra(maps: HashMap<String, f32>) -> Self{
+        round_i16(Raster::<f32>::algebra(maps))
+    }
+
+    fn try_algebra(maps: HashMap<String, f32>) -> Result<Self, RasterError>{
+        Raster::<f32>::try_algebra(maps).map(round_i16)
+    }
+
+    fn algebra_ordered(maps: Vec<(String, f32)>) -> Self{
+        round_i16(Raster::<f32>::algebra_ordered(maps))
+    }
+
+    fn try_algebra_ordered(maps: Vec<(String, f32)>) -> Result<Self, RasterError>{
+        Raster::<f32>::try_algebra_ordered(maps).map(round_i16)
+    }
+
+    fn algebra_nodata(maps: HashMap<String, f32>, nodata: f32) -> Self{
+        round_i16(Raster::<f32>::algebra_nodata(maps, nodata))
+    }
+
+    fn try_algebra_nodata(maps: HashMap<String, f32>, nodata: f32) -> Result<Self, RasterError>{
+        Raster::<f32>::try_algebra_nodata(maps, nodata).map(round_i16)
+    }
+
+    fn algebra_parallel(maps: HashMap<String, f32>) -> Self{
+        round_i16(Raster::<f32>::algebra_parallel(maps))
+    }
+
+    fn weighted_average(maps: HashMap<String, f32>) -> Self{
+        round_i16(Raster::<f32>::weighted_average(maps))
+    }
+
+    fn try_weighted_average(maps: HashMap<String, f32>) -> Result<Self, RasterError>{
+        Raster::<f32>::try_weighted_average(maps).map(round_i16)
+    }
+
+    fn try_algebra_parallel(maps: HashMap<String, f32>) -> Result<Self, RasterError>{
+        Raster::<f32>::try_algebra_parallel(maps).map(round_i16)
+    }
+
+    fn algebra_from_file<P: AsRef<Path>>(config: P) -> Result<Self, RasterError>{
+        Raster::<f32>::algebra_from_file(config).map(round_i16)
+    }
+
+    fn algebra_validated(maps: HashMap<String, f32>, tolerance: f32) -> Result<Self, RasterError>{
+        Raster::<f32>::algebra_validated(maps, tolerance).map(round_i16)
+    }
+
+    fn algebra_with_nodata(maps: HashMap<String, (f32, f32)>, nodata: f32) -> Result<Self, RasterError>{
+        Raster::<f32>::algebra_with_nodata(maps, nodata).map(round_i16)
+    }
+}
+
+impl Raster<f32> {
+    /// Writes the raster as a single Float32 band with the given GDAL driver,
+    /// keeping the georeferencing and the NoData value if there is one.
+    pub fn write<P: AsRef<Path>>(&self, filename: P, driver: &str) -> Result<(), RasterError>{
+        self.to_dataset(filename, driver).map(|_| ())
+    }
+
+    /// Creates the dataset `write` saves when it is dropped, which the MEM
+    /// driver keeps in memory only.
+    pub(crate) fn to_dataset<P: AsRef<Path>>(&self, filename: P, driver: &str) -> Result<Dataset, RasterError>{
+        let (rows, cols) = self.data.dim();
+        let filename = path_str(filename.as_ref())?;
+        let driver = Driver::get(driver).map_err(|error| RasterError::Write(Box::new(error)))?;
+        let dataset = driver.create_with_band_type::<f32>(filename, cols as isize, rows as isize, 1)
+            .map_err(|error| RasterError::Write(Box::new(error)))?;
+        dataset.set_geo_transform(&self.geotransform).map_err(|error| RasterError::Write(Box::new(error)))?;
+        dataset.set_projection(&self.projection).map_err(|error| RasterError::Write(Box::new(error)))?;
+        let buffer = Buffer::new((cols, rows), self.data.iter().cloned().collect());
+
+        dataset.write_raster(1, (0, 0), (cols, rows), &buffer)
+            .map_err(|error| RasterError::Write(Box::new(error)))?;
+        if let Some(nodata) = self.nodata {
+            ffi::set_nodata(&dataset, 1, nodata).map_err(|error| RasterError::Write(Box::new(error)))?;
+        }
+        Ok(dataset)
+    }
+
+    /// Writes the raster as a GeoTIFF.
+    pub fn write_tiff<P: AsRef<Path>>(&self, filename: P) -> Result<(), RasterError>{
+        self.write(filename, "GTiff")
+    }
+
+    /// The pixels in row-major order without copying, or None when the data
+    /// is not in standard layout, for instance after a transposed view.
+    pub fn as_slice(&self) -> Option<&[f32]>{
+        self.data.as_slice()
+    }
+
+    /// The pixels in row-major order as 4 little-endian bytes each, whatever
+    /// the memory layout and the platform byte order. The metadata is left
+    /// out.
+    pub fn to_bytes(&self) -> Vec<u8>{
+        self.data.iter().flat_map(|value| value.to_le_bytes()).collect()
+    }
+
+    /// The bytes of `to_bytes`, consuming the raster, which takes over its
+    /// buffer instead of copying the pixels when it is in standard layout.
+    pub fn into_bytes(self) -> Vec<u8>{
+        let values = if self.data.is_standard_layout() {
+            self.data.into_raw_vec()
+        } else {
+            self.data.iter().cloned().collect()
+        };
+        values.into_iter().flat_map(|value| value.to_le_bytes()).collect()
+    }
+
+    /// Borrows the pixels for ndarray operations without copying.
+    pub fn view<'a>(&'a self) -> ArrayView2<'a, f32>{
+        self.data.view()
+    }
+
+    /// Borrows the pixels mutably without copying. Writing through the view
+    /// changes the raster in place, while its metadata stays the same.
+    pub fn view_mut<'a>(&'a mut self) -> ArrayViewMut2<'a, f32>{
+        self.data.view_mut()
+    }
+
+    /// Copies a view into a raster without NoData value or georeferencing,
+    /// as `From<Array2<f32>>`.
+    pub fn from_view(view: ArrayView2<f32>) -> Raster<f32>{
+        Raster::<f32>::from(view.to_owned())
+    }
+}
+
+impl Raster<f32> {
+    /// Reads band 1 of a raster file held in memory, such as bytes received over
+    /// the network, without writing it to disk. `ext` is the file extension,
+    /// with or without the dot, which some GDAL drivers need to recognize the
+    /// format.
+    pub fn from_memory(bytes: &[u8], ext: &str) -> Result<Raster<f32>, RasterError>{
+        let file = ffi::MemFile::new(bytes, ext)?;
+        Self::try_new(file.path())
+    }
+
+    /// Reads band 1 in physical units, `raw * scale + offset` with the scale
+    /// and offset of the band, like `new` when the band has neither. NoData
+    /// pixels keep the raw NoData value. Panics if the file cannot be read,
+    /// see `try_new_scaled`.
+    pub fn new_scaled<P: AsRef<Path>>(filename: P) -> Raster<f32>{
+        Self::try_new_scaled(filename).unwrap_or_else(|error| panic!("Could not create the raster: {}", error))
+    }
+
+    pub fn try_new_scaled<P: AsRef<Path>>(filename: P) -> Result<Raster<f32>, RasterError>{
+        let filename = filename.as_ref();
+        let raster = Self::try_new(filename)?;
+        let dataset = open_dataset(filename)?;
+        let band = dataset.rasterband(1)?;
+        let (scale, offset) = (band.scale().unwrap_or(1.0), band.offset().unwrap_or(0.0));
+
+        let nodata = raster.nodata_value();
+        Ok(raster.map(|value| {
+            if Some(value) == nodata { value } else { (f64::from(value) * scale + offset) as f32 }
+        }))
+    }
+
+    /// Whether the data are equal pixel by pixel, taking two NaN pixels at the
+    /// same position as equal.
+    pub fn nan_equal(&self, other: &Raster<f32>) -> bool{
+        self.data.dim() == other.data.dim() && zip(&self.data, &other.data).all(|(item1, item2)| {
+            item1 == item2 || (item1.is_nan() && item2.is_nan())
+        })
+    }
+
+    /// `RelativeEq` comparison of the data, as `assert_relative_eq!` does,
+    /// false when the shapes differ.
+    pub fn approx_eq(&self, other: &Raster<f32>, epsilon: f32, max_relative: f32) -> bool{
+        self.relative_eq(other, epsilon, max_relative)
+    }
+
+    /// `AbsDiffEq` comparison of the data, false when the shapes differ.
+    pub fn approx_eq_abs(&self, other: &Raster<f32>, epsilon: f32) -> bool{
+        self.abs_diff_eq(other, epsilon)
+    }
+
+    /// Applies `f` to every pixel, NoData sentinels included, keeping the
+    /// metadata.
+    pub fn map<F>(&self, f: F) -> Raster<f32>
+    where
+        F: Fn(f32) -> f32,
+    {
+        self.with_data(self.data.mapv(f))
+    }
+
+    /// `map` running `f` on the rayon thread pool, worth it for large rasters
+    /// or costly functions.
+    pub fn par_map<F>(&self, f: F) -> Raster<f32>
+    where
+        F: Fn(f32) -> f32 + Sync + Send,
+    {
+        let values: Vec<f32> = match self.data.as_slice() {
+            Some(values) => values.par_iter().map(|&value| f(value)).collect(),
+            None => self.data.iter().cloned().collect::<Vec<f32>>().into_par_iter().map(f).collect(),
+        };
+        let data = Array2::from_shape_vec(self.data.dim(), values).expect("one value per pixel");
+        self.with_data(data)
+    }
+}
+
+impl<T: AbsDiffEq> AbsDiffEq for Raster<T>
+where
+    T::Epsilon: Copy,
+{
+    type Epsilon = T::Epsilon;
+    fn default_epsilon() -> T::Epsilon {
+        T::default_epsilon()
+    }
+    
+    /// Compares the data pixel by pixel, ignoring the NoData value. Rasters of
+    /// different shapes are never equal.
+    fn abs_diff_eq(&self, other: &Self, epsilon: T::Epsilon) -> bool {
+        if self.data.shape() != other.data.shape() {
+            return false;
+        }
+        for (item1, item2) in zip(&self.data, &other.data){
+            if !T::abs_diff_eq(item1, item2, epsilon){
+                return false;
+            }
+        }
+        true
+    }
+}
+
+impl<T: RelativeEq> RelativeEq for Raster<T>
+where
+    T::Epsilon: Copy,
+{
+    fn default_max_relative() -> T::Epsilon {
+        T::default_max_relative()
+    }
+    
+    /// Compares the data pixel by pixel, ignoring the NoData value. Rasters of
+    /// different shapes are never equal.
+    fn relative_eq(&self, other: &Self, epsilon: T::Epsilon, max_relative: T::Epsilon) -> bool {
+        if self.data.shape() != other.data.shape() {
+            return false;
+        }
+        for (item1, item2) in zip(&self.data, &other.data){
+            if !T::relative_eq(item1, item2, epsilon, max_relative){
+                return false;
+            }
+        }
+        true
+    }
+}
+
+impl<T: UlpsEq> UlpsEq for Raster<T>
+where
+    T::Epsilon: Copy,
+{
+    fn default_max_ulps() -> u32 {
+        T::default_max_ulps()
+    }
+    
+    /// Compares the data pixel by pixel, ignoring the NoData value. Rasters of
+    /// different shapes are never equal.
+    fn ulps_eq(&self, other: &Self, epsilon: T::Epsilon, max_ulps: u32) -> bool {
+        if self.data.shape() != other.data.shape() {
+            return false;
+        }
+        for (item1, item2) in zip(&self.data, &other.data){
+            if !T::ulps_eq(item1, item2, epsilon, max_ulps){
+                return false;
+            }
+        }
+        true
+    }
+}
+
+/// Describes the first pixel, in row-major order, where `left` and `right`
+/// differ as `RelativeEq` compares them, or None when they are equal. This is
+/// the message of `assert_raster_eq!`.
+#[doc(hidden)]
+pub fn raster_mismatch<T>(left: &Raster<T>, right: &Raster<T>, epsilon: Option<T::Epsilon>) -> Option<String>
+where
+    T: RelativeEq + Copy + Sub<Output = T> + fmt::Debug,
+    T::Epsilon: Copy,
+{
+    let epsilon = epsilon.unwrap_or_else(T::default_epsilon);
+    let max_relative = T::default_max_relative();
+    if left.relative_eq(right, epsilon, max_relative) {
+        return None;
+    }
+    if left.data.dim() != right.data.dim() {
+        return Some(format!("the shapes differ, {:?} and {:?}", left.data.dim(), right.data.dim()));
+    }
+
+    zip(left.data.indexed_iter(), &right.data)
+        .find(|&((_, value), other)| !value.relative_eq(other, epsilon, max_relative))
+        .map(|(((row, col), &value), &other)| {
+            format!("first difference at (row, col) ({}, {}): left {:?}, right {:?}, delta {:?}", row, col, value, other, value - other)
+        })
+}
+
+/// Asserts that two rasters are equal as `assert_relative_eq!` does, but on
+/// failure reports the first differing `(row, col)` with both values and
+/// their difference, such as `assert_raster_eq!(computed, expected, epsilon
+/// = 1e-5)`.
+#[macro_export]
+macro_rules! assert_raster_eq {
+    ($left:expr, $right:expr) => {
+        if let Some(message) = $crate::raster_mismatch(&$left, &$right, None) {
+            panic!("assertion failed: `assert_raster_eq!({}, {})`, {}", stringify!($left), stringify!($right), message);
+        }
+    };
+    ($left:expr, $right:expr, epsilon = $epsilon:expr) => {
+        if let Some(message) = $crate::raster_mismatch(&$left, &$right, Some($epsilon)) {
+            panic!("assertion failed: `assert_raster_eq!({}, {})`, {}", stringify!($left), stringify!($right), message);
+        }
+    };
+}
+
+#[cfg(test)]
+mod test_approx {
+    use super::*;
+
+    #[test]
+    fn test_assert_raster_eq(){
+        let raster1 = Raster::<f32>::new("data/data1.asc");
+        let mut raster2 = Raster::<f32>::new("data/data1.asc");
+        raster2.data[[1, 2]] += 1e-6;
+
+        assert_raster_eq!(raster1, raster2, epsilon = 1e-5);
+        assert_raster_eq!(raster1, Raster::<f32>::new("data/data1.asc"));
+    }
+
+    #[test]
+    #[should_panic(expected = "first difference at (row, col) (1, 2): left 0.680556, right 0.780556")]
+    fn test_assert_raster_eq_mismatch(){
+        let raster1 = Raster::<f32>::new("data/data1.asc");
+        let mut raster2 = Raster::<f32>::new("data/data1.asc");
+        raster2.data[[1, 2]] = 0.780556;
+        raster2.data[[2, 1]] = 0.0;
+
+        assert_raster_eq!(raster1, raster2, epsilon = 1e-5);
+    }
+
+    #[test]
+    #[should_panic(expected = "the shapes differ, (3, 4) and (2, 3)")]
+    fn test_assert_raster_eq_shape(){
+        assert_raster_eq!(Raster::<f32>::new("data/data1.asc"), Raster::<f32>::new("data/data5.asc"));
+    }
+
+    #[test]
+    fn test_abs_diff_eq(){
+        let raster1 = Raster::<f32>::new("data/data1.asc");
+        let raster2 = Raster::<f32>{
+            data: Array2::from_shape_vec((3, 4), vec![0.388889, 0.513889, 0.638889, 0.805556, 0.444447, 0.583333, 0.680556, -32768.0, 0.625, 0.625, -32768.0, -32768.0]).unwrap(),
+            nodata: None,
+            geotransform: DEFAULT_GEOTRANSFORM,
+            projection: String::new()
+        };
+
+        assert_abs_diff_eq!(raster1, raster2);
+    }
+
+    #[test]
+    fn test_relative_eq(){
+        let raster1 = Raster::<f32>::new("data/data1.asc");
+        let raster2 = Raster::<f32>{
+            data: Array2::from_shape_vec((3, 4), vec![0.388889, 0.513889, 0.638889, 0.805556, 0.444447, 0.583333, 0.680556, -32768.0, 0.625, 0.625, -32768.0, -32768.0]).unwrap(),
+            nodata: None,
+            geotransform: DEFAULT_GEOTRANSFORM,
+            projection: String::new()
+        };
+
+        assert_relative_eq!(raster1, raster2);
+    }
+
+    #[test]
+    fn test_approx_eq(){
+        let raster1 = Raster::<f32>::new("data/data1.asc");
+        let raster2 = Raster::<f32>{
+            data: Array2::from_shape_vec((3, 4), vec![0.388889, 0.513889, 0.638889, 0.805556, 0.444447, 0.583333, 0.680556, -32768.0, 0.625, 0.625, -32768.0, -32768.0]).unwrap(),
+            nodata: None,
+            geotransform: DEFAULT_GEOTRANSFORM,
+            projection: String::new()
+        };
+
+        assert!(raster1.approx_eq(&raster2, f32::EPSILON, f32::EPSILON));
+        assert!(raster1.approx_eq_abs(&raster2, f32::EPSILON));
+
+        let shifted = raster2.map(|value| value + 1e-3);
+        assert!(!raster1.approx_eq(&shifted, f32::EPSILON, 1e-5));
+        assert!(raster1.approx_eq(&shifted, f32::EPSILON, 1e-2));
+        assert!(!raster1.approx_eq_abs(&shifted, 1e-4));
+        assert!(raster1.approx_eq_abs(&shifted, 1e-2));
+        assert!(!raster1.approx_eq_abs(&Raster::from(Array2::zeros((2, 2))), 1.0));
+    }
+
+    #[test]
+    fn test_ulps_eq(){
+        let raster1 = Raster::<f32>::new("data/data1.asc");
+        let raster2 = Raster::<f32>{
+            data: Array2::from_shape_vec((3, 4), vec![0.388889, 0.513889, 0.638889, 0.805556, 0.444447, 0.583333, 0.680556, -32768.0, 0.625, 0.625, -32768.0, -32768.0]).unwrap(),
+            nodata: None,
+            geotransform: DEFAULT_GEOTRANSFORM,
+            projection: String::new()
+        };
+        
+        assert_ulps_eq!(raster1, raster2, max_ulps = 6);
+    }
+
+    #[test]
+    fn test_nan_equal(){
+        let raster1 = Raster::<f32>::from(array![[0.5, f32::NAN], [1.0, 2.0]]);
+        let raster2 = Raster::<f32>::from(array![[0.5, f32::NAN], [1.0, 2.0]]);
+        let raster3 = Raster::<f32>::from(array![[0.5, 0.0], [1.0, 2.0]]);
+
+        assert!(raster1.nan_equal(&raster2));
+        assert!(!raster1.nan_equal(&raster3));
+        assert!(!raster1.nan_equal(&Raster::<f32>::from(array![[0.5, f32::NAN]])));
+        // The approx comparisons keep IEEE semantics.
+        assert!(!raster1.abs_diff_eq(&raster2, f32::EPSILON));
+        assert!(raster1 != raster2);
+    }
+
+    #[test]
+    fn test_shape_mismatch(){
+        let raster1 = Raster::<f32>::new("data/data1.asc");
+        // Equal to the first pixels of data1.asc in row-major order.
+        let raster2 = Raster::<f32>::from(array![[0.388889, 0.513889], [0.638889, 0.805556]]);
+
+        assert!(!raster1.abs_diff_eq(&raster2, 1e-6));
+        assert!(!raster1.relative_eq(&raster2, 1e-6, 1e-6));
+        assert!(!raster1.ulps_eq(&raster2, 1e-6, 6));
+    }
+}
+
+#[cfg(test)]
+mod test_convert {
+    use super::*;
+
+    #[test]
+    fn test_from_array(){
+        let raster: Raster<f32> = array![[0.1, 0.2], [0.3, 0.4]].into();
+        let expected = Raster::<f32>{
+            data: array![[0.1, 0.2], [0.3, 0.4]],
+            nodata: None,
+            geotransform: DEFAULT_GEOTRANSFORM,
+            projection: String::new()
+        };
+
+        assert_eq!(raster, expected);
+    }
+
+    #[test]
+    fn test_into_array(){
+        let raster = Raster::<f32>::new("data/data1.asc");
+        let data: Array2<f32> = raster.into();
+
+        assert_eq!(data.dim(), (3, 4));
+        assert_eq!(data[[0, 0]], 0.388889);
+    }
+
+    #[test]
+    fn test_from_vec(){
+        let raster = Raster::<f32>::from_vec(vec![0.1, 0.2, 0.3, 0.4, 0.5, 0.6], 2, 3).unwrap();
+
+        assert_eq!(raster, Raster::from(array![[0.1, 0.2, 0.3], [0.4, 0.5, 0.6]]));
+    }
+
+    #[test]
+    fn test_shape(){
+        let raster = Raster::<f32>::new("data/data1.asc");
+
+        assert_eq!(raster.shape(), (3, 4));
+        assert_eq!(raster.len(), 12);
+        assert!(!raster.is_empty());
+        assert!(Raster::<f32>::from(Array2::zeros((0, 4))).is_empty());
+    }
+
+    #[test]
+    fn test_from_vec_wrong_length(){
+        match Raster::<f32>::from_vec(vec![0.0; 5], 2, 3) {
+            Err(RasterError::Shape{ expected, got }) => {
+                assert_eq!(expected, (2, 3));
+                assert_eq!(got, 5);
+            }
+            other => panic!("Expected a shape error, got {:?}", other),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test_algebra {
+    use super::*;
+
+    #[test]
+    fn test_new_data(){
+        let weight1: f32 = 0.4;
+        let weight2: f32 = 0.2;
+        let weight3: f32 = 0.2;
+        let weight4: f32 = 0.2;
+        
+        let raster1 = Raster::<f32>::new("data/data1.asc");
+        let raster2 = Raster::<f32>::new("data/data2.asc");
+        let raster3 = Raster::<f32>::new("data/data3.asc");
+        let raster4 = Raster::<f32>::new("data/data4.asc");
+        let result = Raster::<f32>::new("data/result.asc");
+
+        let combination = Raster::<f32>{
+            data: weight1 * raster1.data + weight2 * raster2.data + weight3 * raster3.data + weight4 * raster4.data,
+            nodata: None,
+            geotransform: DEFAULT_GEOTRANSFORM,
+            projection: String::new()
+        };
+
+        assert_relative_eq!(combination, result, epsilon = 1e-5f32);
+
+    }
+
+    #[test]
+    fn test_new_algebra(){
+        let mut maps: HashMap<String, f32> = HashMap::new();
+
+        maps.insert("data/data1.asc".to_string(), 0.4);
+        maps.insert("data/data2.asc".to_string(), 0.2);
+        maps.insert("data/data3.asc".to_string(), 0.2);
+        maps.insert("data/data4.asc".to_string(), 0.2);
+        
+        let result = Raster::<f32>::new("data/result.asc");
+        let combination = Raster::<f32>::algebra(maps);
+
+        assert_relative_eq!(combination, result, epsilon = 1e-5f32);
+    }
+
+    #[test]
+    fn test_algebra_from_file(){
+        let result = Raster::<f32>::new("data/result.asc");
+        let combination = Raster::<f32>::algebra_from_file("data/weights.csv").unwrap();
+
+        assert_relative_eq!(combination, result, epsilon = 1e-5f32);
+    }
+
+    #[test]
+    fn test_algebra_validated(){
+        let mut maps: HashMap<String, f32> = HashMap::new();
+        maps.insert("data/data1.asc".to_string(), 0.4);
+        maps.insert("data/data2.asc".to_string(), 0.2);
+        maps.insert("data/data3.asc".to_string(), 0.2);
+        maps.insert("data/data4.asc".to_string(), 0.2);
+
+        let result = Raster::<f32>::new("data/result.asc");
+        let combination = Raster::<f32>::algebra_validated(maps, 1e-6).unwrap();
+        assert_relative_eq!(combination, result, epsilon = 1e-5f32);
+    }
+
+    #[test]
+    fn test_algebra_validated_negative(){
+        let mut maps: HashMap<String, f32> = HashMap::new();
+        maps.insert("data/data1.asc".to_string(), 1.2);
+        maps.insert("data/data2.asc".to_string(), -0.2);
+
+        match Raster::<f32>::algebra_validated(maps, 1e-6) {
+            Err(RasterError::InvalidWeight{ file, weight }) => {
+                assert_eq!(file, "data/data2.asc");
+                assert_relative_eq!(weight, -0.2, epsilon = 1e-6);
+            }
+            other => panic!("Expected an invalid weight error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_algebra_validated_sum(){
+        let mut maps: HashMap<String, f64> = HashMap::new();
+        maps.insert("data/data1.asc".to_string(), 0.4);
+        maps.insert("data/data2.asc".to_string(), 0.4);
+
+        match Raster::<f64>::algebra_validated(maps.clone(), 0.1) {
+            Err(RasterError::WeightSum{ sum, .. }) => assert_relative_eq!(sum, 0.8),
+            other => panic!("Expected a weight sum error, got {:?}", other),
+        }
+        assert!(Raster::<f64>::algebra_validated(maps.clone(), 0.25).is_ok());
+        assert!(Raster::<f64>::algebra_validated(maps, f64::NAN).is_err());
+    }
+
+    #[test]
+    fn test_algebra_validated_nan(){
+        let mut maps: HashMap<String, f32> = HashMap::new();
+        maps.insert("data/data1.asc".to_string(), f32::NAN);
+        maps.insert("data/data2.asc".to_string(), 1.0);
+
+        match Raster::<f32>::algebra_validated(maps.clone(), 1e-6) {
+            Err(RasterError::InvalidWeight{ file, weight }) => {
+                assert_eq!(file, "data/data1.asc");
+                assert!(weight.is_nan());
+            }
+            other => panic!("Expected an invalid weight error, got {:?}", other),
+        }
+
+        maps.insert("data/data1.asc".to_string(), f32::INFINITY);
+        assert!(Raster::<f32>::algebra_validated(maps, 1e-6).is_err());
+    }
+
+    #[test]
+    fn test_algebra_from_file_malformed(){
+        let config = std::env::temp_dir().join("raster_mapping_malformed.csv");
+        fs::write(&config, "path,weight\ndata/data1.asc,0.4\n\ndata/data2.asc,heavy\n").unwrap();
+
+        match Raster::<f32>::algebra_from_file(&config) {
+            Err(RasterError::Config{ line, message, .. }) => {
+                assert_eq!(line, 4);
+                assert!(message.contains("heavy"));
+            }
+            other => panic!("Expected a config error, got {:?}", other),
+        }
+
+        fs::write(&config, "path,weight\ndata/data1.asc\n").unwrap();
+        match Raster::<f32>::algebra_from_file(&config) {
+            Err(RasterError::Config{ line, .. }) => assert_eq!(line, 2),
+            other => panic!("Expected a config error, got {:?}", other),
+        }
+        fs::remove_file(&config).unwrap();
+    }
+
+    #[test]
+    fn test_algebra_from_missing_file(){
+        match Raster::<f64>::algebra_from_file("data/missing.csv") {
+            Err(RasterError::Io(..)) => (),
+            other => panic!("Expected an io error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_new_data_f64(){
+        let raster1 = Raster::<f64>::new("data/data1.asc");
+        let raster2 = Raster::<f64>::new("data/data2.asc");
+        let combination = Raster::<f64>::algebra_ordered(vec![("data/data1.asc".to_string(), 0.4), ("data/data2.asc".to_string(), 0.6)]);
+        let expected = raster1.with_data(0.4 * raster1.data.clone() + 0.6 * raster2.data);
+
+        assert_relative_eq!(combination, expected, epsilon = 1e-12f64);
+    }
+
+    #[test]
+    fn test_new_algebra_f64(){
+        let mut maps: HashMap<String, f64> = HashMap::new();
+
+        maps.insert("data/data1.asc".to_string(), 0.4);
+        maps.insert("data/data2.asc".to_string(), 0.2);
+        maps.insert("data/data3.asc".to_string(), 0.2);
+        maps.insert("data/data4.asc".to_string(), 0.2);
+
+        let result = Raster::<f64>::new("data/result.asc");
+        let combination = Raster::<f64>::algebra(maps);
+
+        assert_relative_eq!(combination, result, epsilon = 1e-5f64);
+    }
+
+    #[test]
+    fn test_new_algebra_zero(){
+        let maps: HashMap<String, f32> = HashMap::new();
+        let empty: Array2<f32> = array![[]];
+        let result = Raster::<f32>{data: empty, nodata: None, geotransform: DEFAULT_GEOTRANSFORM, projection: String::new()};
+        let combination = Raster::<f32>::algebra(maps);
+
+        assert_eq!(combination, result);
+    }
+
+    #[test]
+    fn test_try_algebra_shape_mismatch(){
+        let mut maps: HashMap<String, f32> = HashMap::new();
+
+        maps.insert("data/data1.asc".to_string(), 0.5);
+        maps.insert("data/data5.asc".to_string(), 0.5);
+
+        match Raster::<f32>::try_algebra(maps) {
+            Err(RasterError::ShapeMismatch{ file, expected, got }) => {
+                if file == "data/data5.asc" {
+                    assert_eq!((expected, got), ((3, 4), (2, 3)));
+                }
+                else {
+                    assert_eq!(file, "data/data1.asc");
+                    assert_eq!((expected, got), ((2, 3), (3, 4)));
+                }
+            }
+            other => panic!("Expected a shape mismatch, got {:?}", other),
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "data/data")]
+    fn test_algebra_shape_mismatch(){
+        let mut maps: HashMap<String, f32> = HashMap::new();
+
+        maps.insert("data/data1.asc".to_string(), 0.5);
+        maps.insert("data/data5.asc".to_string(), 0.5);
+
+        Raster::<f32>::algebra(maps);
+    }
+
+    #[test]
+    fn test_algebra_with_nodata(){
+        let mut maps: HashMap<String, (f32, f32)> = HashMap::new();
+        maps.insert("data/data1.asc".to_string(), (0.5, -32768.0));
+        maps.insert("data/data7.asc".to_string(), (0.5, -9999.0));
+
+        let raster1 = Raster::<f32>::new("data/data1.asc");
+        let raster7 = Raster::<f32>::new("data/data7.asc");
+        let combination = Raster::<f32>::algebra_with_nodata(maps, f32::NAN).unwrap();
+
+        for ((index, &value), (&value1, &value7)) in zip(combination.data.indexed_iter(), zip(&raster1.data, &raster7.data)) {
+            if value1 == -32768.0 || value7 == -9999.0 {
+                assert!(value.is_nan(), "pixel {:?} should be NoData", index);
+            } else {
+                assert_relative_eq!(value, 0.5 * value1 + 0.5 * value7);
+            }
+        }
+        assert_eq!(combination.data.iter().filter(|value| value.is_nan()).count(), 4);
+        assert!(combination.nodata.unwrap().is_nan());
+        assert_eq!(combination.geo_transform(), raster1.geo_transform());
+    }
+
+    #[test]
+    fn test_algebra_ordered(){
+        let maps = vec![("data/data6.asc".to_string(), 0.5), ("data/data1.asc".to_string(), 0.5)];
+        let reversed: Vec<(String, f32)> = maps.iter().rev().cloned().collect();
+
+        let shifted = Raster::<f32>::new("data/data6.asc");
+        let raster = Raster::<f32>::new("data/data1.asc");
+        let combination = Raster::<f32>::algebra_ordered(maps);
+        let combination_reversed = Raster::<f32>::algebra_ordered(reversed);
+
+        assert_relative_eq!(combination, combination_reversed);
+        assert_eq!(combination.geo_transform(), shifted.geo_transform());
+        assert_eq!(combination.nodata, Some(-9999.0));
+        assert_eq!(combination_reversed.geo_transform(), raster.geo_transform());
+        assert_eq!(combination_reversed.nodata, Some(-32768.0));
+    }
+
+    #[test]
+    fn test_algebra_ordered_zero(){
+        let combination = Raster::<f32>::algebra_ordered(vec![]);
+
+        assert_eq!(combination, Raster::<f32>::algebra(HashMap::new()));
+    }
+
+    #[test]
+    fn test_algebra_parallel(){
+        let mut maps: HashMap<String, f32> = HashMap::new();
+
+        maps.insert("data/data1.asc".to_string(), 0.4);
+        maps.insert("data/data2.asc".to_string(), 0.2);
+        maps.insert("data/data3.asc".to_string(), 0.2);
+        maps.insert("data/data4.asc".to_string(), 0.2);
+
+        let combination = Raster::<f32>::algebra(maps.clone());
+        let parallel = Raster::<f32>::algebra_parallel(maps);
+
+        assert_relative_eq!(parallel, combination, epsilon = 1e-3f32);
+        assert_eq!(parallel.geo_transform(), combination.geo_transform());
+        assert_eq!(parallel.nodata, Some(-32768.0));
+    }
+
+    #[test]
+    fn test_algebra_parallel_zero(){
+        let combination = Raster::<f32>::algebra_parallel(HashMap::new());
+
+        assert_eq!(combination, Raster::<f32>::algebra(HashMap::new()));
+    }
+
+    #[test]
+    #[should_panic(expected = "Could not combine the rasters")]
+    fn test_algebra_parallel_mismatch(){
+        let mut maps: HashMap<String, f32> = HashMap::new();
+
+        maps.insert("data/data1.asc".to_string(), 0.5);
+        maps.insert("data/data5.asc".to_string(), 0.5);
+
+        Raster::<f32>::algebra_parallel(maps);
+    }
+
+    #[test]
+    fn test_weighted_average(){
+        let mut maps: HashMap<String, f32> = HashMap::new();
+
+        maps.insert("data/data1.asc".to_string(), 2.0);
+        maps.insert("data/data2.asc".to_string(), 2.0);
+
+        let raster1 = Raster::<f32>::new("data/data1.asc");
+        let raster2 = Raster::<f32>::new("data/data2.asc");
+        let mean = raster1.with_data((&raster1.data + &raster2.data) / 2.0);
+
+        assert_relative_eq!(Raster::<f32>::weighted_average(maps), mean, epsilon = 1e-6f32);
+    }
+
+    #[test]
+    fn test_weighted_average_zero(){
+        let mut maps: HashMap<String, f32> = HashMap::new();
+
+        maps.insert("data/data1.asc".to_string(), 1.0);
+        maps.insert("data/data2.asc".to_string(), -1.0);
+
+        assert_eq!(Raster::<f32>::weighted_average(maps), Raster::<f32>::algebra(HashMap::new()));
+    }
+
+    #[test]
+    fn test_algebra_nodata(){
+        let mut maps: HashMap<String, f32> = HashMap::new();
+
+        maps.insert("data/data1.asc".to_string(), 0.4);
+        maps.insert("data/data2.asc".to_string(), 0.2);
+        maps.insert("data/data3.asc".to_string(), 0.2);
+        maps.insert("data/data4.asc".to_string(), 0.2);
+
+        let result = Raster::<f32>::new("data/result.asc");
+        let combination = Raster::<f32>::algebra_nodata(maps, -32768.0);
+
+        assert_relative_eq!(combination, result, epsilon = 1e-5f32);
+    }
+
+    #[test]
+    fn test_algebra_nodata_all_missing(){
+        let mut maps: HashMap<String, f32> = HashMap::new();
+
+        maps.insert("data/data1.asc".to_string(), 0.3);
+        maps.insert("data/data2.asc".to_string(), 0.3);
+
+        let raster1 = Raster::<f32>::new("data/data1.asc");
+        let raster2 = Raster::<f32>::new("data/data2.asc");
+        let combination = Raster::<f32>::algebra_nodata(maps, -32768.0);
+
+        // Every input is NoData at these pixels.
+        for &position in &[(1, 3), (2, 2), (2, 3)] {
+            assert_eq!(combination.data[position], -32768.0);
+        }
+        assert_relative_eq!(combination.data[(0, 0)], 0.3 * raster1.data[(0, 0)] + 0.3 * raster2.data[(0, 0)]);
+    }
+
+    #[test]
+    fn test_algebra_nodata_zero_weights(){
+        let mut maps: HashMap<String, f32> = HashMap::new();
+
+        maps.insert("data/data1.asc".to_string(), 0.0);
+        maps.insert("data/data2.asc".to_string(), 0.0);
+
+        let combination = Raster::<f32>::algebra_nodata(maps, -32768.0);
+        let expected = Raster::<f32>::new("data/data1.asc")
+            .with_data(Array2::from_shape_vec((3, 4), vec![0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, -32768.0, 0.0, 0.0, -32768.0, -32768.0]).unwrap());
+
+        assert_eq!(combination, expected);
+    }
+
+    #[test]
+    fn test_try_algebra_nodata_errors(){
+        let mut maps: HashMap<String, f32> = HashMap::new();
+        maps.insert("data/data1.asc".to_string(), 0.5);
+        maps.insert("data/data5.asc".to_string(), 0.5);
+
+        match Raster::<f32>::try_algebra_nodata(maps, -32768.0) {
+            Err(RasterError::ShapeMismatch{ .. }) => {}
+            other => panic!("Expected a shape mismatch, got {:?}", other),
+        }
+
+        let mut maps: HashMap<String, f32> = HashMap::new();
+        maps.insert("data/missing.asc".to_string(), 1.0);
+        assert!(Raster::<f32>::try_algebra_nodata(maps, -32768.0).is_err());
+
+        let empty = Raster::<f32>::try_algebra_nodata(HashMap::new(), -32768.0).unwrap();
+        assert_eq!(empty.data.len(), 0);
+    }
+}
+
+#[cfg(test)]
+mod test_scaled {
+    use super::*;
+    use std::env;
+
+    #[test]
+    fn test_new_scaled(){
+        let filename = env::temp_dir().join("raster_mapping_scaled.tif");
+        {
+            let mut raw = Raster::<f32>::from(array![[150.0, 200.0], [0.0, 300.0]]);
+            raw.nodata = Some(0.0);
+            let dataset = raw.to_dataset(&filename, "GTiff").unwrap();
+            unsafe {
+                let band = gdal_sys::GDALGetRasterBand(dataset._c_ptr(), 1);
+                gdal_sys::GDALSetRasterScale(band, 0.01);
+                gdal_sys::GDALSetRasterOffset(band, -1.0);
+            }
+        }
+
+        let raster = Raster::<f32>::new_scaled(&filename);
+        assert_relative_eq!(raster, Raster::from(array![[0.5, 1.0], [0.0, 2.0]]), epsilon = 1e-6);
+        assert_eq!(raster.nodata, Some(0.0));
+        assert_eq!(Raster::<f32>::new(&filename).data[[0, 1]], 200.0);
+        ::std::fs::remove_file(&filename).unwrap();
+    }
+
+    #[test]
+    fn test_new_scaled_without_scale(){
+        assert_eq!(Raster::<f32>::new_scaled("data/data1.asc"), Raster::<f32>::new("data/data1.asc"));
+    }
+}
+
+#[cfg(test)]
+mod test_memory {
+    use super::*;
+
+    #[test]
+    fn test_from_memory(){
+        let bytes = fs::read("data/data1.asc").unwrap();
+        let raster = Raster::<f32>::from_memory(&bytes, "asc").unwrap();
+        let expected = Raster::<f32>::new("data/data1.asc");
+
+        assert_eq!(raster.data, expected.data);
+        assert_eq!(raster.nodata, expected.nodata);
+        assert_eq!(raster.geo_transform(), expected.geo_transform());
+    }
+
+    #[test]
+    fn test_from_memory_invalid(){
+        match Raster::<f32>::from_memory(b"not a raster", ".asc") {
+            Err(RasterError::Open(path, _)) => assert!(path.starts_with("/vsimem")),
+            other => panic!("Expected an open error, got {:?}", other),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test_map {
+    use super::*;
+
+    #[test]
+    fn test_map(){
+        let raster = Raster::<f32>::new("data/data1.asc");
+        let doubled = raster.map(|value| value * 2.0);
+
+        assert_eq!(doubled[(0, 0)], 0.388889 * 2.0);
+        assert_eq!(doubled.nodata, raster.nodata);
+    }
+
+    #[test]
+    fn test_par_map(){
+        let raster = Raster::<f32>::from(Array2::from_shape_fn((50, 40), |(row, col)| (row * 40 + col) as f32));
+
+        assert_eq!(raster.par_map(|value| value * 2.0), raster.map(|value| value * 2.0));
+    }
+
+    #[test]
+    fn test_par_map_transposed(){
+        let raster = Raster::<f32>::from(array![[1.0, 2.0, 3.0], [4.0, 5.0, 6.0]].reversed_axes());
+
+        assert_eq!(raster.par_map(|value| value + 1.0).data, array![[2.0, 5.0], [3.0, 6.0], [4.0, 7.0]]);
+    }
+}
+
+#[cfg(test)]
+mod test_bytes {
+    use super::*;
+
+    #[test]
+    fn test_as_slice(){
+        let raster = Raster::<f32>::new("data/data1.asc");
+
+        assert_eq!(raster.as_slice().unwrap()[..4], [0.388889, 0.513889, 0.638889, 0.805556]);
+        assert!(Raster::<f32>::from(raster.data.reversed_axes()).as_slice().is_none());
+    }
+
+    #[test]
+    fn test_to_bytes(){
+        let raster = Raster::<f32>::new("data/data1.asc");
+        let bytes = raster.to_bytes();
+        let (rows, cols) = raster.shape();
+
+        assert_eq!(bytes.len(), rows * cols * 4);
+        let values: Vec<f32> = bytes.chunks(4).map(|chunk| f32::from_le_bytes([chunk[0], chunk[1], chunk[2], chunk[3]])).collect();
+        assert_eq!(values, raster.data.iter().cloned().collect::<Vec<f32>>());
+        assert_eq!(raster.into_bytes(), bytes);
+    }
+
+    #[test]
+    fn test_into_bytes_transposed(){
+        // Row-major order of the transposed array, not its memory order.
+        let raster = Raster::<f32>::from(array![[1.0, 2.0], [3.0, 4.0]].reversed_axes());
+        let expected: Vec<u8> = [1.0f32, 3.0, 2.0, 4.0].iter().flat_map(|value| value.to_le_bytes()).collect();
+
+        assert_eq!(raster.to_bytes(), expected);
+        assert_eq!(raster.into_bytes(), expected);
+    }
+
+    #[test]
+    fn test_view_mut(){
+        let mut raster = Raster::<f32>::new("data/data1.asc");
+        let original = raster.data.clone();
+        {
+            let mut view = raster.view_mut();
+            view *= 2.0;
+        }
+
+        assert_eq!(raster.data, original * 2.0);
+        assert_eq!(raster.nodata, Some(-32768.0));
+    }
+
+    #[test]
+    fn test_from_view(){
+        let raster = Raster::<f32>::new("data/data1.asc");
+        let copy = Raster::<f32>::from_view(raster.view().slice(s![..2, ..]));
+
+        assert_eq!(copy.data, raster.data.slice(s![..2, ..]));
+        assert_eq!(copy.nodata, None);
+        assert_eq!(copy.geotransform, DEFAULT_GEOTRANSFORM);
+    }
+}
+
+#[cfg(test)]
+mod test_i16 {
+    use super::*;
+
+    #[test]
+    fn test_new_i16(){
+        let raster = Raster::<i16>::new("data/classes1.asc");
+
+        assert_eq!(raster.data, array![[1, 2, 3, 4], [2, 2, 3, -32768], [5, 5, -32768, -32768]]);
+        assert_eq!(raster.nodata, Some(-32768.0));
+        assert_eq!(raster, Raster::<i16>::new("data/classes1.asc"));
+    }
+
+    #[test]
+    fn test_algebra_i16_rounds(){
+        let maps = vec![("data/classes1.asc".to_string(), 0.5), ("data/classes2.asc".to_string(), 0.5)];
+        let combination = Raster::<i16>::try_algebra_ordered(maps).unwrap();
+
+        assert_eq!(combination.data.row(0), array![6, 11, 17, 22]);
+        assert_eq!(combination.nodata, Some(-32768.0));
+    }
+
+    #[test]
+    fn test_algebra_nodata_i16(){
+        let mut maps: HashMap<String, f32> = HashMap::new();
+        maps.insert("data/classes1.asc".to_string(), 2.0);
+        maps.insert("data/classes2.asc".to_string(), 1.0);
+
+        let combination = Raster::<i16>::algebra_nodata(maps, -32768.0);
+        assert_eq!(combination.data, array![[12, 24, 36, 48], [14, 14, 36, -32768], [60, 60, -32768, -32768]]);
+    }
+
+    #[test]
+    fn test_approx_i16(){
+        let raster = Raster::<i16>::new("data/classes1.asc");
+        let mut other = Raster::<i16>::new("data/classes1.asc");
+        other.data[[0, 0]] += 1;
+
+        assert_abs_diff_eq!(raster, other, epsilon = 1);
+        assert_abs_diff_ne!(raster, other);
+    }
+}
+
+#[cfg(test)]
+mod test_fallible {
+    use super::*;
+    use std::path::PathBuf;
+
+    #[test]
+    fn test_try_new(){
+        let raster = Raster::<f32>::try_new("data/data1.asc").unwrap();
+        let expected = Raster::<f32>::new("data/data1.asc");
+
+        assert_relative_eq!(raster, expected);
+    }
+
+    #[test]
+    fn test_try_new_missing(){
+        let result = Raster::<f32>::try_new("data/missing.asc");
+
+        match result {
+            Err(RasterError::Open(path, _)) => assert_eq!(path, Path::new("data/missing.asc")),
+            other => panic!("Expected an open error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_get_band(){
+        let band = Raster::<f32>::get_band("data/stack.vrt", 3);
+        let expected = Raster::<f32>::get_data("data/data3.asc");
+
+        assert_eq!(band, expected);
+    }
+
+    #[test]
+    fn test_new_band(){
+        let raster = Raster::<f32>::new_band("data/stack.vrt", 2);
+        let expected = Raster::<f32>::new("data/data2.asc");
+
+        assert_relative_eq!(raster, expected);
+    }
+
+    #[test]
+    fn test_try_get_band_out_of_range(){
+        for &band in &[0, 5] {
+            match Raster::<f32>::try_get_band("data/stack.vrt", band) {
+                Err(RasterError::Band{ band: requested, count }) => {
+                    assert_eq!(requested, band);
+                    assert_eq!(count, 4);
+                }
+                other => panic!("Expected a band error, got {:?}", other),
+            }
+        }
+    }
+
+    #[test]
+    fn test_nodata(){
+        assert_eq!(Raster::<f32>::nodata("data/data1.asc"), Some(-32768.0));
+    }
+
+    #[test]
+    fn test_new_nodata(){
+        let raster = Raster::<f32>::new("data/data1.asc");
+
+        assert_eq!(raster.nodata, Some(-32768.0));
+    }
+
+    #[test]
+    fn test_new_georeferencing(){
+        let raster = Raster::<f32>::new("data/data1.asc");
+        let expected = [-40.0, 0.28, 0.0, -19.31, 0.0, -0.23];
+
+        for (value, expected) in zip(&raster.geo_transform(), &expected){
+            assert_relative_eq!(value, expected, epsilon = 1e-9);
+        }
+        assert!(raster.projection().contains("WGS_1984"));
+    }
+
+    #[test]
+    fn test_algebra_georeferencing(){
+        let mut maps: HashMap<String, f32> = HashMap::new();
+
+        maps.insert("data/data1.asc".to_string(), 0.5);
+        maps.insert("data/data2.asc".to_string(), 0.5);
+
+        let raster = Raster::<f32>::new("data/data1.asc");
+        let combination = Raster::<f32>::algebra(maps);
+
+        assert_eq!(combination.geo_transform(), raster.geo_transform());
+        assert_eq!(combination.projection(), raster.projection());
+    }
+
+    #[test]
+    fn test_new_path(){
+        let path = PathBuf::from("data").join("data1.asc");
+        let raster = Raster::<f32>::new(&path);
+
+        assert_eq!(raster, Raster::<f32>::new(path.as_path()));
+        assert_eq!(raster, Raster::<f32>::new("data/data1.asc"));
+        assert_eq!(raster, Raster::<f32>::new(String::from("data/data1.asc")));
+        assert_eq!(Raster::<f32>::get_data(path), raster.data);
+    }
+
+    #[test]
+    #[should_panic(expected = "data/missing.asc")]
+    fn test_new_missing(){
+        Raster::<f32>::new("data/missing.asc");
+    }
+
+    #[test]
+    fn test_new_window(){
+        let full = Raster::<f32>::new("data/data1.asc");
+        let window = Raster::<f32>::new_window("data/data1.asc", (1, 1), (2, 2)).unwrap();
+
+        assert_eq!(window.data, full.data.slice(s![1..3, 1..3]).to_owned());
+        assert_eq!(window.nodata, full.nodata);
+        assert_relative_eq!(window.geo_transform()[0], -39.72, epsilon = 1e-9);
+        assert_relative_eq!(window.geo_transform()[3], -19.54, epsilon = 1e-9);
+    }
+
+    #[test]
+    fn test_new_window_outside(){
+        match Raster::<f32>::new_window("data/data1.asc", (3, 1), (2, 2)) {
+            Err(RasterError::Window{ offset, size, raster }) => {
+                assert_eq!(offset, (3, 1));
+                assert_eq!(size, (2, 2));
+                assert_eq!(raster, (4, 3));
+            }
+            other => panic!("Expected a window error, got {:?}", other),
+        }
+        assert!(Raster::<f32>::new_window("data/data1.asc", (-1, 0), (1, 1)).is_err());
+    }
+}
+
+#[cfg(test)]
+mod test_write {
+    use super::*;
+    use std::env;
+
+    #[test]
+    fn test_write_round_trip(){
+        let mut maps: HashMap<String, f32> = HashMap::new();
+
+        maps.insert("data/data1.asc".to_string(), 0.4);
+        maps.insert("data/data2.asc".to_string(), 0.2);
+        maps.insert("data/data3.asc".to_string(), 0.2);
+        maps.insert("data/data4.asc".to_string(), 0.2);
+
+        let combination = Raster::<f32>::algebra_nodata(maps, -32768.0);
+        let filename = env::temp_dir().join("raster_mapping_round_trip.tif");
+
+        combination.write_tiff(&filename).unwrap();
+        let written = Raster::<f32>::new(&filename);
+
+        assert_relative_eq!(written, combination);
+        assert_eq!(written.data.dim(), (3, 4));
+        assert_eq!(written.nodata, Some(-32768.0));
+        assert_eq!(written.geo_transform(), combination.geo_transform());
+        assert_eq!(written.projection(), combination.projection());
+    }
+
+    #[test]
+    fn test_write_unknown_driver(){
+        let raster = Raster::<f32>::new("data/data1.asc");
+        let filename = env::temp_dir().join("raster_mapping_unknown_driver.tif");
+
+        match raster.write(&filename, "NotADriver") {
+            Err(RasterError::Write(_)) => (),
+            other => panic!("Expected a write error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn test_write_invalid_path(){
+        use std::ffi::OsStr;
+        use std::os::unix::ffi::OsStrExt;
+
+        let raster = Raster::<f32>::new("data/data1.asc");
+        let filename = env::temp_dir().join(OsStr::from_bytes(b"raster_mapping_\xff.tif"));
+
+        match raster.write_tiff(&filename) {
+            Err(RasterError::Path(path)) => assert_eq!(path, filename),
+            other => panic!("Expected a path error, got {:?}", other),
+        }
+    }
+}
+
+#[cfg(all(test, feature = "serde"))]
+mod test_serde {
+    use super::*;
+
+    #[test]
+    fn test_round_trip(){
+        let raster = Raster::<f32>::new("data/data1.asc");
+        let json = serde_json::to_string(&raster).unwrap();
+        let decoded: Raster<f32> = serde_json::from_str(&json).unwrap();
+
+        assert_relative_eq!(decoded, raster);
+        assert_eq!(decoded.nodata, raster.nodata);
+        assert_eq!(decoded.geo_transform(), raster.geo_transform());
+        assert_eq!(decoded.projection(), raster.projection());
+    }
+}
@@ -0,0 +1,381 @@
+use itertools::zip;
+use ndarray::Array2;
+use super::{Raster, RasterError};
+
+impl Raster<f32> {
+    /// Builds a mask with 1 where `predicate` holds and 0 elsewhere,
+    /// including on NoData pixels, so the mask has no NoData value.
+    fn compare<F>(&self, predicate: F) -> Raster<u8>
+        where F: Fn(f32) -> bool
+    {
+        let nodata = self.nodata_value();
+
+        self.with_mask(self.data.mapv(|value| {
+            (Some(value) != nodata && predicate(value)) as u8
+        }))
+    }
+
+    /// Mask of the pixels greater than `threshold`.
+    pub fn gt(&self, threshold: f32) -> Raster<u8>{
+        self.compare(|value| value > threshold)
+    }
+
+    /// Mask of the pixels lower than `threshold`.
+    pub fn lt(&self, threshold: f32) -> Raster<u8>{
+        self.compare(|value| value < threshold)
+    }
+
+    /// Mask of the pixels greater than or equal to `threshold`.
+    pub fn ge(&self, threshold: f32) -> Raster<u8>{
+        self.compare(|value| value >= threshold)
+    }
+
+    /// Mask of the pixels lower than or equal to `threshold`.
+    pub fn le(&self, threshold: f32) -> Raster<u8>{
+        self.compare(|value| value <= threshold)
+    }
+
+    /// Mask of the pixels equal to `threshold`.
+    pub fn eq(&self, threshold: f32) -> Raster<u8>{
+        self.compare(|value| value == threshold)
+    }
+
+    /// Sets the pixels where `mask` is 0 to `fill`, leaving the others unchanged.
+    ///
+    /// Panics if the shapes differ, see `try_apply_mask`.
+    pub fn apply_mask(&self, mask: &Raster<u8>, fill: f32) -> Raster<f32>{
+        self.try_apply_mask(mask, fill).unwrap_or_else(|error| panic!("Cannot apply the mask: {}", error))
+    }
+
+    /// Sets the pixels where `mask` is 0 to `fill`, failing if the shapes differ.
+    pub fn try_apply_mask(&self, mask: &Raster<u8>, fill: f32) -> Result<Raster<f32>, RasterError>{
+        self.check_shape(mask)?;
+
+        let mut data = self.data.clone();
+        for (value, &keep) in zip(data.iter_mut(), mask.data.iter()) {
+            if keep == 0 {
+                *value = fill;
+            }
+        }
+        Ok(self.with_data(data))
+    }
+
+    /// Sets the pixels outside `[low, high]` to `outside_fill`, typically the
+    /// NoData value, keeping the NoData pixels as they are.
+    pub fn mask_range(&self, low: f32, high: f32, outside_fill: f32) -> Raster<f32>{
+        let nodata = self.nodata_value();
+        self.with_data(self.data.mapv(|value| {
+            if Some(value) == nodata || (low..=high).contains(&value) { value } else { outside_fill }
+        }))
+    }
+
+    /// 1.0 where the pixel is greater than or equal to `threshold` and 0.0
+    /// elsewhere, as `ge` but staying `f32`. Pixels equal to `nodata` are
+    /// kept as they are.
+    pub fn binarize(&self, threshold: f32, nodata: Option<f32>) -> Raster<f32>{
+        self.with_data(self.data.mapv(|value| match nodata {
+            Some(nodata) if value == nodata => value,
+            _ => if value >= threshold { 1.0 } else { 0.0 },
+        }))
+    }
+}
+
+impl Raster<u8> {
+    /// Combines two masks pixel by pixel, with nonzero pixels as true,
+    /// failing if the shapes differ.
+    fn logical<F>(&self, other: &Raster<u8>, f: F) -> Result<Raster<u8>, RasterError>
+        where F: Fn(bool, bool) -> bool
+    {
+        self.check_shape(other)?;
+
+        let mut data = Array2::zeros(self.data.dim());
+        for (item, (&a, &b)) in zip(&mut data, zip(&self.data, &other.data)) {
+            *item = f(a != 0, b != 0) as u8;
+        }
+        Ok(self.with_mask(data))
+    }
+
+    /// 1 where both masks are nonzero.
+    ///
+    /// Panics if the shapes differ, see `try_and`.
+    pub fn and(&self, other: &Raster<u8>) -> Raster<u8>{
+        self.try_and(other).unwrap_or_else(|error| panic!("Cannot combine the masks: {}", error))
+    }
+
+    /// 1 where both masks are nonzero, failing if the shapes differ.
+    pub fn try_and(&self, other: &Raster<u8>) -> Result<Raster<u8>, RasterError>{
+        self.logical(other, |a, b| a && b)
+    }
+
+    /// 1 where either mask is nonzero.
+    ///
+    /// Panics if the shapes differ, see `try_or`.
+    pub fn or(&self, other: &Raster<u8>) -> Raster<u8>{
+        self.try_or(other).unwrap_or_else(|error| panic!("Cannot combine the masks: {}", error))
+    }
+
+    /// 1 where either mask is nonzero, failing if the shapes differ.
+    pub fn try_or(&self, other: &Raster<u8>) -> Result<Raster<u8>, RasterError>{
+        self.logical(other, |a, b| a || b)
+    }
+
+    /// 1 where exactly one mask is nonzero.
+    ///
+    /// Panics if the shapes differ, see `try_xor`.
+    pub fn xor(&self, other: &Raster<u8>) -> Raster<u8>{
+        self.try_xor(other).unwrap_or_else(|error| panic!("Cannot combine the masks: {}", error))
+    }
+
+    /// 1 where exactly one mask is nonzero, failing if the shapes differ.
+    pub fn try_xor(&self, other: &Raster<u8>) -> Result<Raster<u8>, RasterError>{
+        self.logical(other, |a, b| a != b)
+    }
+
+    /// 1 where the mask is zero.
+    pub fn not(&self) -> Raster<u8>{
+        self.with_mask(self.data.mapv(|value| (value == 0) as u8))
+    }
+
+    /// One pass of a 3x3 structuring element, setting a pixel to 1 when
+    /// `any` or all of its neighbors are foreground. Pixels outside the
+    /// raster count as background.
+    fn morphology(&self, any: bool) -> Raster<u8>{
+        let (rows, cols) = self.data.dim();
+
+        self.with_mask(Array2::from_shape_fn((rows, cols), |(row, col)| {
+            let mut foreground = 0;
+            for r in row as isize - 1..row as isize + 2 {
+                for c in col as isize - 1..col as isize + 2 {
+                    if r >= 0 && c >= 0 && (r as usize) < rows && (c as usize) < cols && self.data[[r as usize, c as usize]] != 0 {
+                        foreground += 1;
+                    }
+                }
+            }
+            if any { (foreground > 0) as u8 } else { (foreground == 9) as u8 }
+        }))
+    }
+
+    /// Grows the nonzero pixels by one 3x3 step per iteration, giving a 0/1 mask.
+    pub fn dilate(&self, iterations: usize) -> Raster<u8>{
+        (0..iterations).fold(self.with_mask(self.data.mapv(|value| (value != 0) as u8)), |mask, _| mask.morphology(true))
+    }
+
+    /// Shrinks the nonzero pixels by one 3x3 step per iteration, giving a 0/1
+    /// mask. Pixels on the raster edges are removed by the first iteration.
+    pub fn erode(&self, iterations: usize) -> Raster<u8>{
+        (0..iterations).fold(self.with_mask(self.data.mapv(|value| (value != 0) as u8)), |mask, _| mask.morphology(false))
+    }
+
+    /// Labels the regions of nonzero pixels connected through their 4 side
+    /// neighbors, or also through the diagonal ones with a `connectivity` of
+    /// 8, from 1 in row-major order of their first pixel. Background pixels
+    /// are 0, and the labels have no NoData value. Returns the labels and the
+    /// number of regions.
+    ///
+    /// Panics if `connectivity` is not 4 or 8.
+    pub fn connected_components(&self, connectivity: u8) -> (Raster<i32>, usize){
+        let neighbors: &[(isize, isize)] = match connectivity {
+            4 => &[(-1, 0), (0, -1), (0, 1), (1, 0)],
+            8 => &[(-1, -1), (-1, 0), (-1, 1), (0, -1), (0, 1), (1, -1), (1, 0), (1, 1)],
+            _ => panic!("The connectivity must be 4 or 8, got {}", connectivity),
+        };
+
+        let (rows, cols) = self.data.dim();
+        let mut labels: Array2<i32> = Array2::zeros((rows, cols));
+        let mut count = 0;
+        let mut stack = Vec::new();
+
+        for ((row, col), &value) in self.data.indexed_iter() {
+            if value == 0 || labels[[row, col]] != 0 {
+                continue;
+            }
+            count += 1;
+            labels[[row, col]] = count as i32;
+            stack.push((row, col));
+
+            while let Some((row, col)) = stack.pop() {
+                for &(dr, dc) in neighbors {
+                    let (r, c) = (row as isize + dr, col as isize + dc);
+                    if r < 0 || c < 0 || r as usize >= rows || c as usize >= cols {
+                        continue;
+                    }
+                    let (r, c) = (r as usize, c as usize);
+                    if self.data[[r, c]] != 0 && labels[[r, c]] == 0 {
+                        labels[[r, c]] = count as i32;
+                        stack.push((r, c));
+                    }
+                }
+            }
+        }
+        (self.with_mask(labels), count)
+    }
+}
+
+#[cfg(test)]
+mod test_mask {
+    use super::*;
+    use ndarray::Array2;
+
+    #[test]
+    fn test_compare(){
+        let raster = Raster::from_vec(vec![0.0, 0.1, 0.2, 0.3, 0.4, 0.5, 0.6, 0.7, 0.8, 0.9, 1.0, 0.5], 3, 4).unwrap();
+
+        assert_eq!(raster.gt(0.5).data.into_raw_vec(), vec![0, 0, 0, 0, 0, 0, 1, 1, 1, 1, 1, 0]);
+        assert_eq!(raster.ge(0.5).data.into_raw_vec(), vec![0, 0, 0, 0, 0, 1, 1, 1, 1, 1, 1, 1]);
+        assert_eq!(raster.lt(0.5).data.into_raw_vec(), vec![1, 1, 1, 1, 1, 0, 0, 0, 0, 0, 0, 0]);
+        assert_eq!(raster.le(0.5).data.into_raw_vec(), vec![1, 1, 1, 1, 1, 1, 0, 0, 0, 0, 0, 1]);
+        assert_eq!(raster.eq(0.5).data.into_raw_vec(), vec![0, 0, 0, 0, 0, 1, 0, 0, 0, 0, 0, 1]);
+    }
+
+    #[test]
+    fn test_compare_nodata(){
+        let raster = Raster{ nodata: Some(-9999.0), ..Raster::from_vec(vec![-9999.0, 0.1, 0.2, 0.3, 0.4, 0.5, 0.6, 0.7, 0.8, 0.9, 1.0, -9999.0], 3, 4).unwrap() };
+
+        assert_eq!(raster.lt(0.5).nodata, None);
+        assert_eq!(raster.lt(0.5).data.into_raw_vec(), vec![0, 1, 1, 1, 1, 0, 0, 0, 0, 0, 0, 0]);
+        assert_eq!(raster.eq(-9999.0).data.into_raw_vec(), vec![0; 12]);
+    }
+
+    #[test]
+    fn test_apply_mask(){
+        let raster = Raster::from_vec(vec![0.0, 0.1, 0.2, 0.3, 0.4, 0.5, 0.6, 0.7, 0.8, 0.9, 1.0, 1.1], 3, 4).unwrap();
+        let mask = raster.gt(0.5);
+        let masked = raster.apply_mask(&mask, -1.0);
+
+        assert_eq!(masked.data.into_raw_vec(), vec![-1.0, -1.0, -1.0, -1.0, -1.0, -1.0, 0.6, 0.7, 0.8, 0.9, 1.0, 1.1]);
+    }
+
+    #[test]
+    fn test_mask_range(){
+        let raster = Raster{ nodata: Some(-9999.0), ..Raster::from_vec(vec![-0.5, 0.0, 0.2, 0.5, 1.0, 1.2, -9999.0, 0.7, 3.0, 0.9, -0.1, 1.0], 3, 4).unwrap() };
+        let masked = raster.mask_range(0.0, 1.0, -9999.0);
+
+        assert_eq!(masked.data.into_raw_vec(), vec![-9999.0, 0.0, 0.2, 0.5, 1.0, -9999.0, -9999.0, 0.7, -9999.0, 0.9, -9999.0, 1.0]);
+    }
+
+    #[test]
+    fn test_mask_range_keeps_nodata(){
+        let raster = Raster{ nodata: Some(-9999.0), ..Raster::from_vec(vec![-9999.0, 5.0, 0.5, 0.5, 0.5, 0.5, 0.5, 0.5, 0.5, 0.5, 0.5, 0.5], 3, 4).unwrap() };
+        let masked = raster.mask_range(0.0, 1.0, f32::NAN);
+
+        assert_eq!(masked[(0, 0)], -9999.0);
+        assert!(masked[(0, 1)].is_nan());
+        assert_eq!(masked.nodata, Some(-9999.0));
+    }
+
+    #[test]
+    fn test_binarize(){
+        let raster = Raster{ nodata: Some(-9999.0), ..Raster::from_vec(vec![-0.5, 0.0, 0.2, 0.5, 1.0, 1.2, -9999.0, 0.7, 3.0, 0.49, 0.51, 0.5], 3, 4).unwrap() };
+        let binary = raster.binarize(0.5, Some(-9999.0));
+
+        assert_eq!(binary.data.into_raw_vec(), vec![0.0, 0.0, 0.0, 1.0, 1.0, 1.0, -9999.0, 1.0, 1.0, 0.0, 1.0, 1.0]);
+        assert_eq!(binary.nodata, Some(-9999.0));
+    }
+
+    #[test]
+    fn test_apply_mask_mismatch(){
+        let raster = Raster::from_vec(vec![0.0; 12], 3, 4).unwrap();
+        let mask = Raster::<u8>::from(Array2::zeros((2, 2)));
+
+        match raster.try_apply_mask(&mask, 0.0) {
+            Err(RasterError::DimensionMismatch{ expected, got }) => {
+                assert_eq!(expected, (3, 4));
+                assert_eq!(got, (2, 2));
+            }
+            other => panic!("Expected a dimension mismatch, got {:?}", other),
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "Cannot apply the mask")]
+    fn test_apply_mask_panics(){
+        let raster = Raster::from_vec(vec![0.0; 12], 3, 4).unwrap();
+        let mask = Raster::<u8>::from(Array2::zeros((2, 2)));
+
+        raster.apply_mask(&mask, 0.0);
+    }
+
+    #[test]
+    fn test_dilate(){
+        let mut mask = Raster::<u8>::from(Array2::zeros((5, 5)));
+        mask.data[[2, 2]] = 1;
+
+        let dilated = mask.dilate(1);
+        assert_eq!(dilated.data.slice(s![1..4, 1..4]), Array2::<u8>::ones((3, 3)));
+        assert_eq!(dilated.data.iter().filter(|&&value| value == 1).count(), 9);
+        assert_eq!(mask.dilate(2).data, Array2::<u8>::ones((5, 5)));
+
+        mask.nodata = Some(255.0);
+        assert_eq!(mask.dilate(1).nodata, None);
+    }
+
+    #[test]
+    fn test_erode(){
+        let mut mask = Raster::<u8>::from(Array2::zeros((5, 5)));
+        mask.data.slice_mut(s![1..4, 1..4]).fill(1);
+        mask.data[[0, 4]] = 1;
+
+        let eroded = mask.erode(1);
+        assert_eq!(eroded.data.iter().filter(|&&value| value == 1).count(), 1);
+        assert_eq!(eroded.data[[2, 2]], 1);
+        assert_eq!(mask.erode(2).data, Array2::<u8>::zeros((5, 5)));
+        assert_eq!(Raster::<u8>::from(Array2::ones((3, 3))).erode(1).data, array![[0, 0, 0], [0, 1, 0], [0, 0, 0]]);
+    }
+
+    #[test]
+    fn test_connected_components(){
+        let mut mask = Raster::<u8>::from(array![[1, 1, 0, 0],
+                                                 [0, 1, 0, 1],
+                                                 [0, 0, 1, 1]]);
+        mask.nodata = Some(255.0);
+
+        let (labels, count) = mask.connected_components(4);
+        assert_eq!(count, 2);
+        assert_eq!(labels.data, array![[1, 1, 0, 0], [0, 1, 0, 2], [0, 0, 2, 2]]);
+        assert_eq!(labels.nodata, None);
+
+        let (labels, count) = mask.connected_components(8);
+        assert_eq!(count, 1);
+        assert!(labels.data.iter().all(|&label| label == 0 || label == 1));
+    }
+
+    #[test]
+    #[should_panic(expected = "connectivity")]
+    fn test_connected_components_connectivity(){
+        Raster::<u8>::from(Array2::zeros((2, 2))).connected_components(6);
+    }
+
+    #[test]
+    fn test_logical(){
+        let mask1 = Raster::<u8>::from(array![[0, 0], [1, 1]]);
+        let mask2 = Raster::<u8>::from(array![[0, 1], [0, 2]]);
+
+        assert_eq!(mask1.and(&mask2).data, array![[0, 0], [0, 1]]);
+        assert_eq!(mask1.or(&mask2).data, array![[0, 1], [1, 1]]);
+        assert_eq!(mask1.xor(&mask2).data, array![[0, 1], [1, 0]]);
+        assert_eq!(mask2.not().data, array![[1, 0], [1, 0]]);
+    }
+
+    #[test]
+    #[should_panic(expected = "Cannot combine the masks")]
+    fn test_logical_mismatch(){
+        let mask1 = Raster::<u8>::from(array![[0, 0], [1, 1]]);
+        let mask2 = Raster::<u8>::from(array![[0, 1, 0]]);
+
+        mask1.and(&mask2);
+    }
+
+    #[test]
+    fn test_try_logical(){
+        let mask1 = Raster::<u8>::from(array![[0, 0], [1, 1]]);
+        let mask2 = Raster::<u8>::from(array![[0, 1, 0]]);
+
+        assert_eq!(mask1.try_or(&mask1).unwrap(), mask1);
+        for result in &[mask1.try_and(&mask2), mask1.try_or(&mask2), mask1.try_xor(&mask2)] {
+            match *result {
+                Err(RasterError::DimensionMismatch{ expected, got }) => assert_eq!((expected, got), ((2, 2), (1, 3))),
+                ref other => panic!("Expected a dimension mismatch, got {:?}", other),
+            }
+        }
+    }
+}
@@ -0,0 +1,116 @@
+use std::collections::HashMap;
+use std::path::Path;
+use gdal::metadata::Metadata;
+use super::{ffi, open_dataset, RasterError};
+
+/// A band of a dataset, as `band_info` lists it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BandInfo {
+    /// 1-based index, as `Mapping::new_band` takes it.
+    pub index: isize,
+    /// Description of the band, often empty.
+    pub description: String,
+    /// GDAL name of the pixel type, such as "Float32".
+    pub data_type: String,
+    pub nodata: Option<f64>,
+}
+
+/// Metadata items of a `domain`, or of the default domain with None, as GDAL
+/// reports them for the dataset. Panics if the file cannot be opened, see
+/// `try_metadata`.
+pub fn metadata<P: AsRef<Path>>(filename: P, domain: Option<&str>) -> HashMap<String, String>{
+    let filename = filename.as_ref();
+    try_metadata(filename, domain).unwrap_or_else(|error| panic!("Could not read the metadata of {}: {}", filename.display(), error))
+}
+
+/// Metadata items of a domain, empty when the dataset has none. Items without
+/// a `=` are kept as keys with an empty value.
+pub fn try_metadata<P: AsRef<Path>>(filename: P, domain: Option<&str>) -> Result<HashMap<String, String>, RasterError>{
+    let dataset = open_dataset(filename)?;
+    let items = ffi::metadata(&dataset, domain.unwrap_or(""))?;
+
+    Ok(items.into_iter().map(|item| {
+        let mut parts = item.splitn(2, '=');
+        let key = parts.next().unwrap_or("").to_string();
+        (key, parts.next().unwrap_or("").to_string())
+    }).collect())
+}
+
+/// A single metadata item, None when it is not set. Panics if the file cannot
+/// be opened, see `try_metadata_item`.
+pub fn metadata_item<P: AsRef<Path>>(filename: P, key: &str, domain: Option<&str>) -> Option<String>{
+    let filename = filename.as_ref();
+    try_metadata_item(filename, key, domain).unwrap_or_else(|error| panic!("Could not read the metadata of {}: {}", filename.display(), error))
+}
+
+pub fn try_metadata_item<P: AsRef<Path>>(filename: P, key: &str, domain: Option<&str>) -> Result<Option<String>, RasterError>{
+    Ok(open_dataset(filename)?.metadata_item(key, domain.unwrap_or("")))
+}
+
+/// Describes the bands `1..=count` of the dataset without reading their
+/// pixels.
+pub fn band_info<P: AsRef<Path>>(filename: P) -> Result<Vec<BandInfo>, RasterError>{
+    let dataset = open_dataset(filename)?;
+
+    (1..=dataset.count()).map(|index| {
+        let band = dataset.rasterband(index)?;
+        Ok(BandInfo{
+            index,
+            description: band.description()?,
+            data_type: ffi::data_type_name(band.band_type()),
+            nodata: band.no_data_value()
+        })
+    }).collect()
+}
+
+#[cfg(test)]
+mod test_metadata {
+    use super::*;
+    use std::env;
+    use Raster;
+
+    #[test]
+    fn test_metadata(){
+        let filename = env::temp_dir().join("raster_mapping_metadata.tif");
+        {
+            let raster = Raster::<f32>::from(array![[1.0, 2.0], [3.0, 4.0]]);
+            let mut dataset = raster.to_dataset(&filename, "GTiff").unwrap();
+            dataset.set_metadata_item("ACQUISITION_DATE", "2018-06-01", "").unwrap();
+            dataset.set_metadata_item("SENSOR", "MSI", "PROVENANCE").unwrap();
+        }
+
+        let items = metadata(&filename, None);
+        assert_eq!(items.get("ACQUISITION_DATE").map(String::as_str), Some("2018-06-01"));
+        assert!(!items.contains_key("SENSOR"));
+        assert_eq!(metadata(&filename, Some("PROVENANCE")).get("SENSOR").map(String::as_str), Some("MSI"));
+
+        assert_eq!(metadata_item(&filename, "ACQUISITION_DATE", None), Some("2018-06-01".to_string()));
+        assert_eq!(metadata_item(&filename, "SENSOR", Some("PROVENANCE")), Some("MSI".to_string()));
+        assert_eq!(metadata_item(&filename, "MISSING", None), None);
+        ::std::fs::remove_file(&filename).unwrap();
+    }
+
+    #[test]
+    fn test_metadata_empty(){
+        assert!(metadata("data/data1.asc", Some("PROVENANCE")).is_empty());
+    }
+
+    #[test]
+    fn test_metadata_missing_file(){
+        assert!(try_metadata("data/missing.asc", None).is_err());
+        assert!(try_metadata_item("data/missing.asc", "KEY", None).is_err());
+    }
+
+    #[test]
+    fn test_band_info(){
+        let bands = band_info("data/stack.vrt").unwrap();
+
+        assert_eq!(bands.len(), open_dataset("data/stack.vrt").unwrap().count() as usize);
+        assert_eq!(bands.iter().map(|band| band.index).collect::<Vec<isize>>(), vec![1, 2, 3, 4]);
+        for band in &bands {
+            assert_eq!(band.data_type, "Float32");
+            assert_eq!(band.nodata, Some(-32768.0));
+        }
+        assert!(band_info("data/missing.asc").is_err());
+    }
+}
@@ -0,0 +1,109 @@
+use std::path::Path;
+use itertools::zip;
+use ndarray::Array2;
+use super::{Mapping, Raster, RasterError};
+
+/// Distance in pixels below which a tile origin is taken to be on the grid.
+const GRID_TOLERANCE: f64 = 1e-6;
+
+impl Raster<f32> {
+    /// Merges north-up tiles on the pixel grid of the first one into a raster
+    /// covering the union of their extents. Pixels covered by no tile are set
+    /// to `nodata`, the output NoData value. Where tiles overlap, the last
+    /// tile in `paths` with a valid pixel wins, so a NoData or NaN pixel never
+    /// hides a value written before it. The projection is the one of the first
+    /// tile. Zero files give the same empty raster as `algebra`.
+    pub fn mosaic<P: AsRef<Path>>(paths: &[P], nodata: f32) -> Result<Raster<f32>, RasterError>{
+        let tiles = paths.iter().map(Self::try_new).collect::<Result<Vec<Raster<f32>>, RasterError>>()?;
+        let grid = match tiles.first() {
+            Some(tile) => tile.geotransform,
+            None => return Self::try_algebra_ordered(Vec::new()),
+        };
+
+        // Offset of each tile, in pixels of the first one.
+        let mut offsets = Vec::with_capacity(tiles.len());
+        for (path, tile) in zip(paths, &tiles) {
+            let geotransform = tile.geotransform;
+            let col = (geotransform[0] - grid[0]) / grid[1];
+            let row = (geotransform[3] - grid[3]) / grid[5];
+            let aligned = geotransform[2] == 0.0 && geotransform[4] == 0.0
+                && (geotransform[1] - grid[1]).abs() <= GRID_TOLERANCE * grid[1].abs()
+                && (geotransform[5] - grid[5]).abs() <= GRID_TOLERANCE * grid[5].abs()
+                && (col - col.round()).abs() <= GRID_TOLERANCE
+                && (row - row.round()).abs() <= GRID_TOLERANCE;
+            if grid[2] != 0.0 || grid[4] != 0.0 || !aligned {
+                return Err(RasterError::Grid{ file: path.as_ref().display().to_string() });
+            }
+            offsets.push((row.round() as isize, col.round() as isize));
+        }
+
+        let top = offsets.iter().map(|offset| offset.0).min().unwrap_or(0);
+        let left = offsets.iter().map(|offset| offset.1).min().unwrap_or(0);
+        let bottom = zip(&offsets, &tiles).map(|(offset, tile)| offset.0 + tile.data.rows() as isize).max().unwrap_or(0);
+        let right = zip(&offsets, &tiles).map(|(offset, tile)| offset.1 + tile.data.cols() as isize).max().unwrap_or(0);
+
+        let mut data = Array2::from_elem(((bottom - top) as usize, (right - left) as usize), nodata);
+        for (&(row, col), tile) in zip(&offsets, &tiles) {
+            let tile_nodata = tile.nodata_value();
+            let (row, col) = ((row - top) as usize, (col - left) as usize);
+            for ((r, c), &value) in tile.data.indexed_iter() {
+                if Some(value) != tile_nodata && !value.is_nan() {
+                    data[[row + r, col + c]] = value;
+                }
+            }
+        }
+
+        let mut geotransform = grid;
+        geotransform[0] += left as f64 * grid[1];
+        geotransform[3] += top as f64 * grid[5];
+
+        Ok(Raster::<f32>{
+            data,
+            nodata: Some(f64::from(nodata)),
+            geotransform,
+            projection: tiles[0].projection.clone()
+        })
+    }
+}
+
+#[cfg(test)]
+mod test_mosaic {
+    use super::*;
+
+    #[test]
+    fn test_mosaic(){
+        let mosaic = Raster::<f32>::mosaic(&["data/tile1.asc", "data/tile2.asc"], -32768.0).unwrap();
+
+        assert_eq!(mosaic.data, array![[1.0, 2.0, 5.0, 6.0], [3.0, 4.0, 7.0, -32768.0]]);
+        assert_eq!(mosaic.nodata, Some(-32768.0));
+        assert_eq!(mosaic.geotransform, Raster::<f32>::new("data/tile1.asc").geotransform);
+    }
+
+    #[test]
+    fn test_mosaic_order(){
+        // The first tile is to the right, so the origin moves to the second.
+        let mosaic = Raster::<f32>::mosaic(&["data/tile2.asc", "data/tile1.asc"], f32::NAN).unwrap();
+
+        assert_eq!(mosaic.row(0), array![1.0, 2.0, 5.0, 6.0]);
+        assert!(mosaic[(1, 3)].is_nan());
+        assert_eq!(mosaic.geotransform[0], -40.0);
+    }
+
+    #[test]
+    fn test_mosaic_overlap(){
+        let mosaic = Raster::<f32>::mosaic(&["data/tile1.asc", "data/tile1.asc", "data/tile2.asc"], -1.0).unwrap();
+        assert_eq!(mosaic.data.dim(), (2, 4));
+
+        // Both share their NoData pixels, so data2 overwrites all of data1.
+        let mosaic = Raster::<f32>::mosaic(&["data/data1.asc", "data/data2.asc"], -32768.0).unwrap();
+        assert_eq!(mosaic, Raster::<f32>::new("data/data2.asc"));
+    }
+
+    #[test]
+    fn test_mosaic_off_grid(){
+        match Raster::<f32>::mosaic(&["data/tile1.asc", "data/data1.asc"], -32768.0) {
+            Err(RasterError::Grid{ file }) => assert_eq!(file, "data/data1.asc"),
+            other => panic!("Expected a grid error, got {:?}", other),
+        }
+    }
+}
@@ -0,0 +1,349 @@
+use std::ops::{Add, Div, Index, IndexMut, Mul, Sub};
+use itertools::zip;
+use ndarray::{Array1, Array2, ArrayView1};
+use super::{Raster, RasterError};
+
+impl Raster<f32> {
+    /// Adds two rasters pixel by pixel, failing if their shapes differ.
+    pub fn checked_add(&self, other: &Raster<f32>) -> Result<Raster<f32>, RasterError>{
+        self.check_shape(other)?;
+        Ok(self.with_data(&self.data + &other.data))
+    }
+
+    /// Subtracts `other` pixel by pixel, failing if their shapes differ.
+    pub fn checked_sub(&self, other: &Raster<f32>) -> Result<Raster<f32>, RasterError>{
+        self.check_shape(other)?;
+        Ok(self.with_data(&self.data - &other.data))
+    }
+
+    /// Divides by `other` pixel by pixel, failing if their shapes differ.
+    /// Where `other` is zero the pixel is this raster's NoData value, or NaN
+    /// without one.
+    pub fn checked_div(&self, other: &Raster<f32>) -> Result<Raster<f32>, RasterError>{
+        self.check_shape(other)?;
+
+        let fill = self.nodata_value().unwrap_or(f32::NAN);
+        let mut data = Array2::zeros(self.data.dim());
+        for (item, (&numerator, &denominator)) in zip(&mut data, zip(&self.data, &other.data)) {
+            *item = if denominator == 0.0 { fill } else { numerator / denominator };
+        }
+        Ok(self.with_data(data))
+    }
+
+    /// Multiplies by a raster of spatially varying weights pixel by pixel.
+    ///
+    /// Panics if the shapes differ, see `try_weighted_by`.
+    pub fn weighted_by(&self, weight: &Raster<f32>) -> Raster<f32>{
+        self.try_weighted_by(weight).unwrap_or_else(|error| panic!("Cannot weight the raster: {}", error))
+    }
+
+    /// Multiplies by `weight` pixel by pixel, failing if the shapes differ. A
+    /// pixel equal to the NoData value of either raster is this raster's
+    /// NoData value, or NaN without one.
+    pub fn try_weighted_by(&self, weight: &Raster<f32>) -> Result<Raster<f32>, RasterError>{
+        self.check_shape(weight)?;
+
+        let (nodata, weight_nodata) = (self.nodata_value(), weight.nodata_value());
+        let fill = nodata.unwrap_or(f32::NAN);
+        let mut data = Array2::zeros(self.data.dim());
+        for (item, (&value, &factor)) in zip(&mut data, zip(&self.data, &weight.data)) {
+            *item = if Some(value) == nodata || Some(factor) == weight_nodata { fill } else { value * factor };
+        }
+        Ok(self.with_data(data))
+    }
+
+    /// Copies the pixels of row `r`.
+    ///
+    /// Panics if the row is out of range.
+    pub fn row(&self, r: usize) -> Array1<f32>{
+        self.row_view(r).to_owned()
+    }
+
+    /// Copies the pixels of column `c`.
+    ///
+    /// Panics if the column is out of range.
+    pub fn col(&self, c: usize) -> Array1<f32>{
+        self.col_view(c).to_owned()
+    }
+
+    /// Borrows row `r` without copying, panicking if it is out of range.
+    pub fn row_view<'a>(&'a self, r: usize) -> ArrayView1<'a, f32>{
+        let (rows, _) = self.data.dim();
+        assert!(r < rows, "row {} is out of range, the raster has {} rows", r, rows);
+        self.data.row(r)
+    }
+
+    /// Borrows column `c` without copying, panicking if it is out of range.
+    pub fn col_view<'a>(&'a self, c: usize) -> ArrayView1<'a, f32>{
+        let (_, cols) = self.data.dim();
+        assert!(c < cols, "column {} is out of range, the raster has {} columns", c, cols);
+        self.data.column(c)
+    }
+}
+
+impl Add<Raster<f32>> for Raster<f32> {
+    type Output = Raster<f32>;
+
+    fn add(self, other: Raster<f32>) -> Raster<f32>{
+        self + &other
+    }
+}
+
+impl Add<&Raster<f32>> for Raster<f32> {
+    type Output = Raster<f32>;
+
+    /// Panics if the shapes differ, see `checked_add`.
+    fn add(self, other: &Raster<f32>) -> Raster<f32>{
+        self.checked_add(other).unwrap_or_else(|error| panic!("Cannot add the rasters: {}", error))
+    }
+}
+
+impl Sub<Raster<f32>> for Raster<f32> {
+    type Output = Raster<f32>;
+
+    fn sub(self, other: Raster<f32>) -> Raster<f32>{
+        self - &other
+    }
+}
+
+impl Sub<&Raster<f32>> for Raster<f32> {
+    type Output = Raster<f32>;
+
+    /// Panics if the shapes differ, see `checked_sub`.
+    fn sub(self, other: &Raster<f32>) -> Raster<f32>{
+        self.checked_sub(other).unwrap_or_else(|error| panic!("Cannot subtract the rasters: {}", error))
+    }
+}
+
+impl Div<Raster<f32>> for Raster<f32> {
+    type Output = Raster<f32>;
+
+    fn div(self, other: Raster<f32>) -> Raster<f32>{
+        self / &other
+    }
+}
+
+impl Div<&Raster<f32>> for Raster<f32> {
+    type Output = Raster<f32>;
+
+    /// Panics if the shapes differ, see `checked_div`.
+    fn div(self, other: &Raster<f32>) -> Raster<f32>{
+        self.checked_div(other).unwrap_or_else(|error| panic!("Cannot divide the rasters: {}", error))
+    }
+}
+
+/// Scales every pixel, NoData sentinels included.
+impl Mul<f32> for Raster<f32> {
+    type Output = Raster<f32>;
+
+    fn mul(self, factor: f32) -> Raster<f32>{
+        Raster::<f32>{
+            data: self.data * factor,
+            ..self
+        }
+    }
+}
+
+impl Mul<Raster<f32>> for f32 {
+    type Output = Raster<f32>;
+
+    fn mul(self, raster: Raster<f32>) -> Raster<f32>{
+        raster * self
+    }
+}
+
+/// Pixel at `(row, col)`, panicking out of bounds like `Array2`.
+impl Index<(usize, usize)> for Raster<f32> {
+    type Output = f32;
+
+    fn index(&self, index: (usize, usize)) -> &f32{
+        &self.data[index]
+    }
+}
+
+impl IndexMut<(usize, usize)> for Raster<f32> {
+    fn index_mut(&mut self, index: (usize, usize)) -> &mut f32{
+        &mut self.data[index]
+    }
+}
+
+#[cfg(test)]
+mod test_ops {
+    use super::*;
+    use ndarray::Array2;
+    use Mapping;
+
+    #[test]
+    fn test_add(){
+        let raster1 = Raster::from_vec(vec![0.1, 0.2, 0.3, 0.4, 0.5, 0.6, 0.7, 0.8, 0.9, 1.0, 1.1, 1.2], 3, 4).unwrap();
+        let raster2 = Raster::from_vec(vec![1.0, 1.0, 1.0, 1.0, 2.0, 2.0, 2.0, 2.0, 0.0, 0.0, 0.0, 0.0], 3, 4).unwrap();
+        let expected = Raster::from_vec(vec![1.1, 1.2, 1.3, 1.4, 2.5, 2.6, 2.7, 2.8, 0.9, 1.0, 1.1, 1.2], 3, 4).unwrap();
+
+        assert_relative_eq!(raster1 + raster2, expected);
+    }
+
+    #[test]
+    fn test_add_reference(){
+        let raster1 = Raster::from_vec(vec![0.1, 0.2, 0.3, 0.4, 0.5, 0.6, 0.7, 0.8, 0.9, 1.0, 1.1, 1.2], 3, 4).unwrap();
+        let raster2 = Raster::from_vec(vec![0.1, 0.2, 0.3, 0.4, 0.5, 0.6, 0.7, 0.8, 0.9, 1.0, 1.1, 1.2], 3, 4).unwrap();
+        let expected = Raster::from_vec(vec![0.2, 0.4, 0.6, 0.8, 1.0, 1.2, 1.4, 1.6, 1.8, 2.0, 2.2, 2.4], 3, 4).unwrap();
+
+        assert_relative_eq!(raster1 + &raster2, expected);
+    }
+
+    #[test]
+    fn test_checked_add_mismatch(){
+        let raster1 = Raster::from_vec(vec![0.0; 12], 3, 4).unwrap();
+        let raster2 = Raster::<f32>::from(Array2::zeros((2, 2)));
+
+        match raster1.checked_add(&raster2) {
+            Err(RasterError::DimensionMismatch{ expected, got }) => {
+                assert_eq!(expected, (3, 4));
+                assert_eq!(got, (2, 2));
+            }
+            other => panic!("Expected a dimension mismatch, got {:?}", other),
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "Cannot add the rasters")]
+    fn test_add_mismatch(){
+        let raster1 = Raster::from_vec(vec![0.0; 12], 3, 4).unwrap();
+        let raster2 = Raster::<f32>::from(Array2::zeros((2, 2)));
+
+        let _ = raster1 + raster2;
+    }
+
+    #[test]
+    fn test_sub(){
+        let after = Raster::from_vec(vec![1.1, 1.2, 1.3, 1.4, 2.5, 2.6, 2.7, 2.8, 0.9, 1.0, 1.1, 1.2], 3, 4).unwrap();
+        let before = Raster::from_vec(vec![1.0, 1.0, 1.0, 1.0, 2.0, 2.0, 2.0, 2.0, 0.0, 0.0, 0.0, 0.0], 3, 4).unwrap();
+        let expected = Raster::from_vec(vec![0.1, 0.2, 0.3, 0.4, 0.5, 0.6, 0.7, 0.8, 0.9, 1.0, 1.1, 1.2], 3, 4).unwrap();
+
+        assert_relative_eq!(after - &before, expected, epsilon = 1e-6f32);
+        assert_relative_eq!(before - Raster::from_vec(vec![0.0; 12], 3, 4).unwrap(), Raster::from_vec(vec![1.0, 1.0, 1.0, 1.0, 2.0, 2.0, 2.0, 2.0, 0.0, 0.0, 0.0, 0.0], 3, 4).unwrap());
+    }
+
+    #[test]
+    fn test_div(){
+        let raster1 = Raster::from_vec(vec![1.0, 2.0, 3.0, 4.0, 5.0, 6.0, 7.0, 8.0, 9.0, 10.0, 11.0, 12.0], 3, 4).unwrap();
+        let raster2 = Raster::from_vec(vec![2.0, 2.0, 2.0, 2.0, 4.0, 4.0, 4.0, 4.0, 0.5, 0.5, 0.5, 0.5], 3, 4).unwrap();
+        let expected = Raster::from_vec(vec![0.5, 1.0, 1.5, 2.0, 1.25, 1.5, 1.75, 2.0, 18.0, 20.0, 22.0, 24.0], 3, 4).unwrap();
+
+        assert_relative_eq!(raster1 / raster2, expected);
+    }
+
+    #[test]
+    fn test_div_zero(){
+        let mut raster1 = Raster::from_vec(vec![1.0; 12], 3, 4).unwrap();
+        let mut raster2 = Raster::from_vec(vec![2.0; 12], 3, 4).unwrap();
+        raster2[(0, 1)] = 0.0;
+
+        let ratio = raster1.checked_div(&raster2).unwrap();
+        assert!(ratio[(0, 1)].is_nan());
+        assert_eq!(ratio[(0, 0)], 0.5);
+
+        raster1.nodata = Some(-9999.0);
+        assert_eq!((raster1 / &raster2)[(0, 1)], -9999.0);
+    }
+
+    #[test]
+    fn test_weighted_by(){
+        let mut raster1 = Raster::from_vec(vec![1.0, 2.0, 3.0, 4.0, 5.0, -9999.0, 7.0, 8.0, 9.0, 10.0, 11.0, 12.0], 3, 4).unwrap();
+        raster1.nodata = Some(-9999.0);
+        let mut weight = Raster::from_vec(vec![1.0, 0.5, 0.25, 0.0, 2.0, 2.0, -1.0, 1.0, 0.1, 0.1, 0.1, 0.5], 3, 4).unwrap();
+        weight.nodata = Some(-1.0);
+        let expected = Raster::from_vec(vec![1.0, 1.0, 0.75, 0.0, 10.0, -9999.0, -9999.0, 8.0, 0.9, 1.0, 1.1, 6.0], 3, 4).unwrap();
+
+        let weighted = raster1.weighted_by(&weight);
+        assert_relative_eq!(weighted, expected);
+        assert_eq!(weighted.nodata, Some(-9999.0));
+        assert!(Raster::from_vec(vec![1.0; 12], 3, 4).unwrap().weighted_by(&weight)[(1, 2)].is_nan());
+    }
+
+    #[test]
+    #[should_panic(expected = "Cannot weight the raster")]
+    fn test_weighted_by_mismatch(){
+        Raster::from_vec(vec![0.0; 12], 3, 4).unwrap().weighted_by(&Raster::<f32>::from(Array2::zeros((2, 2))));
+    }
+
+    #[test]
+    #[should_panic(expected = "Cannot subtract the rasters")]
+    fn test_sub_mismatch(){
+        let raster1 = Raster::from_vec(vec![0.0; 12], 3, 4).unwrap();
+        let raster2 = Raster::<f32>::from(Array2::zeros((2, 2)));
+
+        let _ = raster1 - raster2;
+    }
+
+    #[test]
+    fn test_checked_div_mismatch(){
+        let raster1 = Raster::from_vec(vec![0.0; 12], 3, 4).unwrap();
+        let raster2 = Raster::<f32>::from(Array2::zeros((2, 2)));
+
+        assert!(raster1.checked_div(&raster2).is_err());
+    }
+
+    #[test]
+    fn test_mul_zero(){
+        let raster1 = Raster::from_vec(vec![0.1, 0.2, 0.3, 0.4, 0.5, 0.6, 0.7, 0.8, 0.9, 1.0, 1.1, 1.2], 3, 4).unwrap();
+
+        assert_relative_eq!(raster1 * 0.0, Raster::from_vec(vec![0.0; 12], 3, 4).unwrap());
+    }
+
+    #[test]
+    fn test_mul_one(){
+        let raster1 = Raster::from_vec(vec![0.1, 0.2, 0.3, 0.4, 0.5, 0.6, 0.7, 0.8, 0.9, 1.0, 1.1, 1.2], 3, 4).unwrap();
+        let expected = Raster::from_vec(vec![0.1, 0.2, 0.3, 0.4, 0.5, 0.6, 0.7, 0.8, 0.9, 1.0, 1.1, 1.2], 3, 4).unwrap();
+
+        assert_relative_eq!(1.0 * raster1, expected);
+    }
+
+    #[test]
+    fn test_mul_fraction(){
+        let raster1 = Raster::<f32>::new("data/data1.asc");
+        // The NoData sentinel is scaled like any other value.
+        let expected = Raster::from_vec(vec![0.1555556, 0.2055556, 0.2555556, 0.3222224, 0.1777788, 0.2333332, 0.2722224, -13107.2, 0.25, 0.25, -13107.2, -13107.2], 3, 4).unwrap();
+
+        assert_relative_eq!(0.4 * raster1, expected, epsilon = 1e-5f32);
+    }
+
+    #[test]
+    fn test_index(){
+        let mut raster1 = Raster::from_vec(vec![0.1, 0.2, 0.3, 0.4, 0.5, 0.6, 0.7, 0.8, 0.9, 1.0, 1.1, 1.2], 3, 4).unwrap();
+        assert_eq!(raster1[(1, 2)], 0.7);
+
+        raster1[(1, 2)] = 5.0;
+        assert_eq!(raster1[(1, 2)], 5.0);
+        assert_eq!(raster1.data[[1, 2]], 5.0);
+    }
+
+    #[test]
+    fn test_row_col(){
+        let raster1 = Raster::<f32>::new("data/data1.asc");
+
+        assert_eq!(raster1.row(1), array![0.444447, 0.583333, 0.680556, -32768.0]);
+        assert_eq!(raster1.col(2), array![0.638889, 0.680556, -32768.0]);
+        assert_eq!(raster1.row_view(1), raster1.row(1));
+        assert_eq!(raster1.col_view(2), raster1.col(2));
+    }
+
+    #[test]
+    #[should_panic(expected = "row 3 is out of range")]
+    fn test_row_out_of_range(){
+        let _ = Raster::from_vec(vec![0.0; 12], 3, 4).unwrap().row(3);
+    }
+
+    #[test]
+    #[should_panic(expected = "column 4 is out of range")]
+    fn test_col_out_of_range(){
+        let _ = Raster::from_vec(vec![0.0; 12], 3, 4).unwrap().col(4);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_index_out_of_bounds(){
+        let raster1 = Raster::from_vec(vec![0.0; 12], 3, 4).unwrap();
+        let _ = raster1[(3, 0)];
+    }
+}
@@ -0,0 +1,106 @@
+use ndarray::Array2;
+use super::Raster;
+
+impl Raster<f32> {
+    /// Reverses the order of the rows. The geotransform is flipped with them,
+    /// so every pixel keeps its world coordinates and a south-up grid becomes
+    /// north-up.
+    pub fn flip_vertical(&self) -> Raster<f32>{
+        let (rows, cols) = self.data.dim();
+        let mut raster = self.with_data(Array2::from_shape_fn((rows, cols), |(row, col)| self.data[[rows - 1 - row, col]]));
+
+        let gt = &mut raster.geotransform;
+        gt[0] += rows as f64 * gt[2];
+        gt[3] += rows as f64 * gt[5];
+        gt[2] = -gt[2];
+        gt[5] = -gt[5];
+        raster
+    }
+
+    /// Reverses the order of the columns, flipping the geotransform with them.
+    pub fn flip_horizontal(&self) -> Raster<f32>{
+        let (rows, cols) = self.data.dim();
+        let mut raster = self.with_data(Array2::from_shape_fn((rows, cols), |(row, col)| self.data[[row, cols - 1 - col]]));
+
+        let gt = &mut raster.geotransform;
+        gt[0] += cols as f64 * gt[1];
+        gt[3] += cols as f64 * gt[4];
+        gt[1] = -gt[1];
+        gt[4] = -gt[4];
+        raster
+    }
+
+    /// Swaps rows and columns. The column and row terms of the geotransform
+    /// are swapped too, keeping the origin.
+    pub fn transpose(&self) -> Raster<f32>{
+        let (rows, cols) = self.data.dim();
+        let mut raster = self.with_data(Array2::from_shape_fn((cols, rows), |(row, col)| self.data[[col, row]]));
+
+        let gt = &mut raster.geotransform;
+        gt.swap(1, 2);
+        gt.swap(4, 5);
+        raster
+    }
+}
+
+#[cfg(test)]
+mod test_orient {
+    use super::*;
+    use Mapping;
+
+    #[test]
+    fn test_flip_vertical(){
+        let raster = Raster{
+            nodata: Some(-9999.0),
+            geotransform: [100.0, 10.0, 0.0, 50.0, 0.0, -5.0],
+            ..Raster::from_vec((1..13).map(|value| value as f32).collect(), 3, 4).unwrap()
+        };
+        let flipped = raster.flip_vertical();
+
+        assert_eq!(flipped.data, array![[9.0, 10.0, 11.0, 12.0], [5.0, 6.0, 7.0, 8.0], [1.0, 2.0, 3.0, 4.0]]);
+        assert_eq!(flipped.geo_transform(), [100.0, 10.0, 0.0, 35.0, 0.0, 5.0]);
+        assert_eq!(flipped.nodata, Some(-9999.0));
+        assert_eq!(flipped.flip_vertical(), raster);
+    }
+
+    #[test]
+    fn test_flip_horizontal(){
+        let raster = Raster{
+            nodata: Some(-9999.0),
+            geotransform: [100.0, 10.0, 0.0, 50.0, 0.0, -5.0],
+            ..Raster::from_vec((1..13).map(|value| value as f32).collect(), 3, 4).unwrap()
+        };
+        let flipped = raster.flip_horizontal();
+
+        assert_eq!(flipped.data, array![[4.0, 3.0, 2.0, 1.0], [8.0, 7.0, 6.0, 5.0], [12.0, 11.0, 10.0, 9.0]]);
+        assert_eq!(flipped.geo_transform(), [140.0, -10.0, 0.0, 50.0, 0.0, -5.0]);
+        assert_eq!(flipped.flip_horizontal(), raster);
+    }
+
+    #[test]
+    fn test_flip_keeps_world_coordinates(){
+        let raster1 = Raster::<f32>::new("data/data1.asc");
+        let flipped = raster1.flip_vertical();
+
+        let (x, y) = flipped.pixel_to_world(2, 1);
+        let (expected_x, expected_y) = raster1.pixel_to_world(0, 1);
+        assert_relative_eq!(x, expected_x);
+        assert_relative_eq!(y, expected_y, epsilon = 1e-9);
+        assert_eq!(flipped.sample(-39.5, -19.9, ::Interp::Nearest), raster1.sample(-39.5, -19.9, ::Interp::Nearest));
+    }
+
+    #[test]
+    fn test_transpose(){
+        let raster = Raster{
+            nodata: Some(-9999.0),
+            geotransform: [100.0, 10.0, 0.0, 50.0, 0.0, -5.0],
+            ..Raster::from_vec((1..13).map(|value| value as f32).collect(), 3, 4).unwrap()
+        };
+        let transposed = raster.transpose();
+
+        assert_eq!(transposed.data, array![[1.0, 5.0, 9.0], [2.0, 6.0, 10.0], [3.0, 7.0, 11.0], [4.0, 8.0, 12.0]]);
+        assert_eq!(transposed.geo_transform(), [100.0, 0.0, 10.0, 50.0, -5.0, 0.0]);
+        assert!(transposed.as_slice().is_some());
+        assert_eq!(transposed.transpose(), raster);
+    }
+}
@@ -0,0 +1,96 @@
+use std::path::Path;
+use itertools::zip;
+use super::{check_shapes, Mapping, Raster, RasterError};
+
+impl Raster<f32> {
+    /// Pixel by pixel maximum of the files, the fuzzy OR. Panics if a file
+    /// cannot be read or the shapes differ, see `try_max_overlay`.
+    pub fn max_overlay<P: AsRef<Path>>(paths: &[P]) -> Raster<f32>{
+        Self::try_max_overlay(paths).unwrap_or_else(|error| panic!("Could not combine the rasters: {}", error))
+    }
+
+    /// Pixel by pixel maximum of the files, see `try_overlay`.
+    pub fn try_max_overlay<P: AsRef<Path>>(paths: &[P]) -> Result<Raster<f32>, RasterError>{
+        Self::try_overlay(paths, f32::max)
+    }
+
+    /// Pixel by pixel minimum of the files, the fuzzy AND. Panics if a file
+    /// cannot be read or the shapes differ, see `try_min_overlay`.
+    pub fn min_overlay<P: AsRef<Path>>(paths: &[P]) -> Raster<f32>{
+        Self::try_min_overlay(paths).unwrap_or_else(|error| panic!("Could not combine the rasters: {}", error))
+    }
+
+    /// Pixel by pixel minimum of the files, see `try_overlay`.
+    pub fn try_min_overlay<P: AsRef<Path>>(paths: &[P]) -> Result<Raster<f32>, RasterError>{
+        Self::try_overlay(paths, f32::min)
+    }
+
+    /// Folds the files pixel by pixel with `f`, checking first that they all
+    /// have the same dimensions. A pixel equal to its file's NoData value makes
+    /// the output pixel NoData. The result takes the georeferencing and the
+    /// NoData value, or NaN without one, of the first file. Zero files give
+    /// the same empty raster as `algebra`.
+    fn try_overlay<P, F>(paths: &[P], f: F) -> Result<Raster<f32>, RasterError>
+        where P: AsRef<Path>, F: Fn(f32, f32) -> f32
+    {
+        check_shapes(paths)?;
+
+        let first = match paths.first() {
+            Some(path) => Self::try_new(path)?,
+            None => return Self::try_algebra_ordered(Vec::new()),
+        };
+        let fill = first.nodata_value().unwrap_or(f32::NAN);
+        let mut result = first.with_data(first.data.mapv(|value| if Some(value) == first.nodata_value() { fill } else { value }));
+        result.nodata = Some(f64::from(fill));
+
+        for path in &paths[1..] {
+            let raster = Self::try_new(path)?;
+            let nodata = raster.nodata_value();
+            for (item, &value) in zip(&mut result.data, &raster.data) {
+                if Some(value) == nodata || *item == fill || item.is_nan() {
+                    *item = fill;
+                } else {
+                    *item = f(*item, value);
+                }
+            }
+        }
+        Ok(result)
+    }
+}
+
+#[cfg(test)]
+mod test_overlay {
+    use super::*;
+
+    #[test]
+    fn test_max_overlay(){
+        let paths = vec!["data/data1.asc".to_string(), "data/data2.asc".to_string()];
+        let combination = Raster::<f32>::max_overlay(&paths);
+
+        assert_eq!(combination.row(0), array![0.788889, 0.776543, 0.751852, 0.805556]);
+        assert_eq!(combination[(2, 3)], -32768.0);
+        assert_eq!(combination.nodata, Some(-32768.0));
+    }
+
+    #[test]
+    fn test_min_overlay(){
+        let combination = Raster::<f32>::min_overlay(&["data/data1.asc", "data/data2.asc"]);
+
+        assert_eq!(combination.row(0), array![0.388889, 0.513889, 0.638889, 0.702469]);
+        assert_eq!(combination.row(2), array![0.282716, 0.625, -32768.0, -32768.0]);
+    }
+
+    #[test]
+    fn test_overlay_single_and_empty(){
+        assert_eq!(Raster::<f32>::max_overlay(&["data/data1.asc"]), Raster::<f32>::new("data/data1.asc"));
+        assert!(Raster::<f32>::max_overlay::<&str>(&[]).is_empty());
+    }
+
+    #[test]
+    fn test_overlay_mismatch(){
+        match Raster::<f32>::try_min_overlay(&["data/data1.asc", "data/data5.asc"]) {
+            Err(RasterError::ShapeMismatch{ got, .. }) => assert_eq!(got, (2, 3)),
+            other => panic!("Expected a shape mismatch, got {:?}", other),
+        }
+    }
+}
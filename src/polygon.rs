@@ -0,0 +1,151 @@
+use std::collections::BTreeMap;
+use super::Raster;
+
+type Vertex = (usize, usize);
+
+/// Turns `(drow, dcol)` a quarter to the right on the screen, where rows grow
+/// downwards.
+fn turn_right(direction: (isize, isize)) -> (isize, isize){
+    (direction.1, -direction.0)
+}
+
+fn step(from: Vertex, to: Vertex) -> (isize, isize){
+    (to.0 as isize - from.0 as isize, to.1 as isize - from.1 as isize)
+}
+
+impl Raster<u8> {
+    /// Boundary edges between the pixel corners, as `(row, col)`, walked
+    /// clockwise on the screen around the nonzero pixels, keyed by their start.
+    fn boundary_edges(&self) -> BTreeMap<Vertex, Vec<Vertex>>{
+        let (rows, cols) = self.data.dim();
+        let background = |row: isize, col: isize| {
+            row < 0 || col < 0 || row as usize >= rows || col as usize >= cols || self.data[[row as usize, col as usize]] == 0
+        };
+
+        let mut edges: BTreeMap<Vertex, Vec<Vertex>> = BTreeMap::new();
+        for ((row, col), &value) in self.data.indexed_iter() {
+            if value == 0 {
+                continue;
+            }
+            let (r, c) = (row as isize, col as isize);
+            let sides = [
+                (background(r - 1, c), (row, col), (row, col + 1)),
+                (background(r, c + 1), (row, col + 1), (row + 1, col + 1)),
+                (background(r + 1, c), (row + 1, col + 1), (row + 1, col)),
+                (background(r, c - 1), (row + 1, col), (row, col)),
+            ];
+            for &(open, from, to) in &sides {
+                if open {
+                    edges.entry(from).or_default().push(to);
+                }
+            }
+        }
+        edges
+    }
+
+    /// Outlines of the nonzero regions as rings of world coordinates `(x, y)`
+    /// through the pixel corners, without repeating the first vertex and
+    /// without collinear vertices. Regions touching only at a corner get
+    /// separate rings, as with 4-connectivity. Holes are rings of their own,
+    /// traced in the opposite direction from the outer boundaries.
+    pub fn polygonize(&self) -> Vec<Vec<(f64, f64)>>{
+        let mut edges = self.boundary_edges();
+        let mut rings = Vec::new();
+
+        while let Some(start) = edges.keys().next().cloned() {
+            let mut ring = vec![start];
+            let mut current = start;
+            let mut direction = (0, 1);
+
+            loop {
+                let targets = edges.get_mut(&current).expect("every boundary vertex has an outgoing edge");
+                // Turning right first keeps regions touching at a corner apart.
+                let preferred = [turn_right(direction), direction, turn_right(turn_right(turn_right(direction)))];
+                let index = preferred.iter()
+                    .filter_map(|&wanted| targets.iter().position(|&target| step(current, target) == wanted))
+                    .next()
+                    .unwrap_or(0);
+                let next = targets.swap_remove(index);
+                if targets.is_empty() {
+                    edges.remove(&current);
+                }
+
+                direction = step(current, next);
+                current = next;
+                if current == start {
+                    break;
+                }
+                ring.push(current);
+            }
+
+            let corners: Vec<Vertex> = (0..ring.len()).filter(|&index| {
+                let previous = ring[(index + ring.len() - 1) % ring.len()];
+                let next = ring[(index + 1) % ring.len()];
+                step(previous, ring[index]) != step(ring[index], next)
+            }).map(|index| ring[index]).collect();
+
+            let gt = self.geotransform;
+            rings.push(corners.into_iter().map(|(row, col)| {
+                let (row, col) = (row as f64, col as f64);
+                (gt[0] + col * gt[1] + row * gt[2], gt[3] + col * gt[4] + row * gt[5])
+            }).collect());
+        }
+        rings
+    }
+}
+
+#[cfg(test)]
+mod test_polygon {
+    use super::*;
+    use ndarray::Array2;
+
+    #[test]
+    fn test_polygonize_rectangle(){
+        let blob = Raster{
+            geotransform: [100.0, 10.0, 0.0, 200.0, 0.0, -10.0],
+            ..Raster::<u8>::from(array![[0, 0, 0, 0, 0],
+                                        [0, 1, 1, 1, 0],
+                                        [0, 1, 1, 1, 0],
+                                        [0, 0, 0, 0, 0]])
+        };
+
+        assert_eq!(blob.polygonize(), vec![vec![(110.0, 190.0), (140.0, 190.0), (140.0, 170.0), (110.0, 170.0)]]);
+    }
+
+    #[test]
+    fn test_polygonize_edges_and_shape(){
+        let l_shape = Raster{
+            geotransform: [100.0, 10.0, 0.0, 200.0, 0.0, -10.0],
+            ..Raster::<u8>::from(array![[1, 0],
+                                        [1, 1]])
+        };
+
+        assert_eq!(l_shape.polygonize(), vec![vec![(100.0, 200.0), (110.0, 200.0), (110.0, 190.0), (120.0, 190.0), (120.0, 180.0), (100.0, 180.0)]]);
+        assert!(Raster::<u8>::from(Array2::zeros((2, 2))).polygonize().is_empty());
+    }
+
+    #[test]
+    fn test_polygonize_hole(){
+        let ring = Raster{
+            geotransform: [100.0, 10.0, 0.0, 200.0, 0.0, -10.0],
+            ..Raster::<u8>::from(array![[1, 1, 1],
+                                        [1, 0, 1],
+                                        [1, 1, 1]])
+        };
+        let rings = ring.polygonize();
+
+        assert_eq!(rings.len(), 2);
+        assert_eq!(rings[0], vec![(100.0, 200.0), (130.0, 200.0), (130.0, 170.0), (100.0, 170.0)]);
+        assert_eq!(rings[1], vec![(110.0, 190.0), (110.0, 180.0), (120.0, 180.0), (120.0, 190.0)]);
+    }
+
+    #[test]
+    fn test_polygonize_diagonal(){
+        let diagonal = Raster::<u8>::from(array![[1, 0],
+                                                 [0, 1]]);
+        let rings = diagonal.polygonize();
+
+        assert_eq!(rings.len(), 2);
+        assert!(rings.iter().all(|ring| ring.len() == 4));
+    }
+}
@@ -0,0 +1,108 @@
+use ndarray::Array2;
+use super::Raster;
+
+/// How the pixels of a block are combined by `Raster::aggregate`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AggMethod {
+    Mean,
+    Max,
+    Min,
+    Sum,
+}
+
+impl AggMethod {
+    /// Combines a non-empty list of values.
+    fn apply(self, values: &[f32]) -> f32{
+        match self {
+            AggMethod::Mean => {
+                (values.iter().map(|&value| f64::from(value)).sum::<f64>() / values.len() as f64) as f32
+            }
+            AggMethod::Max => values.iter().cloned().fold(f32::NEG_INFINITY, f32::max),
+            AggMethod::Min => values.iter().cloned().fold(f32::INFINITY, f32::min),
+            AggMethod::Sum => values.iter().map(|&value| f64::from(value)).sum::<f64>() as f32,
+        }
+    }
+}
+
+impl Raster<f32> {
+    /// Coarsens the raster by combining each `factor` x `factor` block into one
+    /// pixel, giving `rows / factor` by `cols / factor` pixels. The remainder
+    /// rows and columns at the bottom and right edges are dropped. NoData pixels
+    /// are left out of each block, and a block with no valid pixel becomes
+    /// NoData. The pixel size in the geotransform is scaled by `factor`.
+    ///
+    /// Panics if `factor` is 0.
+    pub fn aggregate(&self, factor: usize, method: AggMethod) -> Raster<f32>{
+        assert!(factor > 0, "The aggregation factor must be positive");
+
+        let nodata = self.nodata_value();
+        let (rows, cols) = self.data.dim();
+        let data = Array2::from_shape_fn((rows / factor, cols / factor), |(row, col)| {
+            let block = self.data.slice(s![row * factor..(row + 1) * factor, col * factor..(col + 1) * factor]);
+            let values: Vec<f32> = block.iter().cloned().filter(|&value| Some(value) != nodata).collect();
+
+            match nodata {
+                Some(nodata) if values.is_empty() => nodata,
+                _ => method.apply(&values),
+            }
+        });
+
+        let mut raster = self.with_data(data);
+        for index in &[1, 2, 4, 5] {
+            raster.geotransform[*index] *= factor as f64;
+        }
+        raster
+    }
+}
+
+#[cfg(test)]
+mod test_resample {
+    use super::*;
+
+    #[test]
+    fn test_aggregate_mean(){
+        let raster = Raster{
+            geotransform: [-40.0, 0.5, 0.0, -19.0, 0.0, -0.25],
+            ..Raster::from_vec((1..17).map(|value| value as f32).collect(), 4, 4).unwrap()
+        };
+        let aggregated = raster.aggregate(2, AggMethod::Mean);
+
+        assert_eq!(aggregated.data, array![[3.5, 5.5], [11.5, 13.5]]);
+        assert_eq!(aggregated.geotransform, [-40.0, 1.0, 0.0, -19.0, 0.0, -0.5]);
+    }
+
+    #[test]
+    fn test_aggregate_methods(){
+        let raster = Raster::from_vec((1..17).map(|value| value as f32).collect(), 4, 4).unwrap();
+
+        assert_eq!(raster.aggregate(2, AggMethod::Max).data, array![[6.0, 8.0], [14.0, 16.0]]);
+        assert_eq!(raster.aggregate(2, AggMethod::Min).data, array![[1.0, 3.0], [9.0, 11.0]]);
+        assert_eq!(raster.aggregate(2, AggMethod::Sum).data, array![[14.0, 22.0], [46.0, 54.0]]);
+    }
+
+    #[test]
+    fn test_aggregate_nodata(){
+        let raster = Raster{
+            nodata: Some(-9999.0),
+            ..Raster::from_vec(vec![1.0, -9999.0, -9999.0, -9999.0,
+                                    5.0, 6.0, -9999.0, -9999.0,
+                                    9.0, 10.0, 11.0, 12.0,
+                                    13.0, 14.0, 15.0, 16.0], 4, 4).unwrap()
+        };
+
+        assert_eq!(raster.aggregate(2, AggMethod::Mean).data, array![[4.0, -9999.0], [11.5, 13.5]]);
+    }
+
+    #[test]
+    fn test_aggregate_remainder(){
+        let raster = Raster::from_vec((0..15).map(|value| value as f32).collect(), 3, 5).unwrap();
+
+        assert_eq!(raster.aggregate(2, AggMethod::Sum).data, array![[12.0, 20.0]]);
+    }
+
+    #[test]
+    #[should_panic(expected = "factor")]
+    fn test_aggregate_zero(){
+        Raster::from_vec(vec![0.0], 1, 1).unwrap().aggregate(0, AggMethod::Mean);
+    }
+}
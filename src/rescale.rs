@@ -0,0 +1,191 @@
+use std::cmp::Ordering;
+use super::Raster;
+use stats::percentile;
+
+impl Raster<f32> {
+    /// Rescales the pixels not equal to `nodata` with `(x - min) / (max - min)`
+    /// over those pixels, so they span [0, 1]. When all of them are equal they
+    /// become 0. NoData pixels are kept as they are.
+    pub fn normalize(&self, nodata: Option<f32>) -> Raster<f32>{
+        let stats = self.stats(nodata);
+        let range = stats.max - stats.min;
+
+        self.with_data(self.data.mapv(|value| {
+            if Some(value) == nodata {
+                value
+            } else if range > 0.0 {
+                (value - stats.min) / range
+            } else {
+                0.0
+            }
+        }))
+    }
+
+    /// Clamps the pixels not equal to `nodata` between their `low_pct` and
+    /// `high_pct` percentiles, in [0, 100] and interpolated linearly between
+    /// the closest ranks. NoData pixels are kept as they are.
+    pub fn percentile_clip(&self, low_pct: f32, high_pct: f32, nodata: Option<f32>) -> Raster<f32>{
+        let mut sorted = self.valid_values(nodata);
+        if sorted.is_empty() {
+            return self.with_data(self.data.clone());
+        }
+        sorted.sort_by(|a, b| a.partial_cmp(b).unwrap_or(Ordering::Equal));
+
+        let (low, high) = (percentile(&sorted, low_pct), percentile(&sorted, high_pct));
+        self.with_data(self.data.mapv(|value| {
+            if Some(value) == nodata {
+                value
+            } else {
+                value.max(low).min(high)
+            }
+        }))
+    }
+
+    /// Clips every pixel into `[min, max]`, except those equal to the raster's
+    /// NoData value.
+    pub fn clamp(&self, min: f32, max: f32) -> Raster<f32>{
+        let nodata = self.nodata_value();
+
+        self.with_data(self.data.mapv(|value| {
+            if Some(value) == nodata {
+                value
+            } else {
+                value.max(min).min(max)
+            }
+        }))
+    }
+
+    /// Quantizes to 8 bits, mapping `[min, max]` linearly onto `[0, 255]` and
+    /// clamping the values outside. With a `nodata` value, the pixels equal
+    /// to it become `fill`, which is then the output NoData value, so valid
+    /// pixels quantized to `fill` read as NoData too: pick a fill at a level
+    /// the data does not reach. `from_u8` maps back.
+    ///
+    /// Panics if `max` is not greater than `min`.
+    pub fn to_u8(&self, min: f32, max: f32, nodata: Option<f32>, fill: u8) -> Raster<u8>{
+        assert!(max > min, "The maximum must be greater than the minimum");
+
+        let mut raster = self.with_data(self.data.mapv(|value| match nodata {
+            Some(nodata) if value == nodata => fill,
+            _ => ((value - min) / (max - min) * 255.0).round().clamp(0.0, 255.0) as u8,
+        }));
+        raster.nodata = nodata.map(|_| f64::from(fill));
+        raster
+    }
+}
+
+impl Raster<u8> {
+    /// Maps 8 bit values back onto `[min, max]`, the inverse of `to_u8` within
+    /// half a step of `(max - min) / 255`. Pixels equal to the NoData value
+    /// become NaN, the output NoData value.
+    pub fn from_u8(&self, min: f32, max: f32) -> Raster<f32>{
+        let nodata = self.nodata;
+
+        let mut raster = self.with_data(self.data.mapv(|value| {
+            if Some(f64::from(value)) == nodata {
+                f32::NAN
+            } else {
+                min + f32::from(value) / 255.0 * (max - min)
+            }
+        }));
+        raster.nodata = self.nodata.map(|_| f64::NAN);
+        raster
+    }
+}
+
+#[cfg(test)]
+mod test_rescale {
+    use super::*;
+
+    #[test]
+    fn test_normalize(){
+        let raster1 = Raster{ nodata: Some(-9999.0), ..Raster::from_vec(vec![2.0, 4.0, 6.0, 10.0, 3.0, 7.0, 8.0, -9999.0, 2.0, 5.0, 10.0, 9.0], 3, 4).unwrap() };
+        let expected = Raster{ nodata: Some(-9999.0), ..Raster::from_vec(vec![0.0, 0.25, 0.5, 1.0, 0.125, 0.625, 0.75, -9999.0, 0.0, 0.375, 1.0, 0.875], 3, 4).unwrap() };
+
+        assert_relative_eq!(raster1.normalize(Some(-9999.0)), expected);
+    }
+
+    #[test]
+    fn test_normalize_constant(){
+        let raster1 = Raster::from_vec(vec![0.5; 12], 3, 4).unwrap();
+
+        assert_relative_eq!(raster1.normalize(None), Raster::from_vec(vec![0.0; 12], 3, 4).unwrap());
+    }
+
+    #[test]
+    fn test_clamp(){
+        let raster1 = Raster{ nodata: Some(-32768.0), ..Raster::from_vec(vec![-0.5, 0.0, 0.3, 1.0, 1.2, 0.7, -32768.0, 0.1, 2.0, -0.1, 0.9, 0.5], 3, 4).unwrap() };
+        let expected = Raster{ nodata: Some(-32768.0), ..Raster::from_vec(vec![0.0, 0.0, 0.3, 1.0, 1.0, 0.7, -32768.0, 0.1, 1.0, 0.0, 0.9, 0.5], 3, 4).unwrap() };
+
+        assert_relative_eq!(raster1.clamp(0.0, 1.0), expected);
+    }
+
+    #[test]
+    fn test_percentile_clip(){
+        let raster1 = Raster::from_vec(vec![1.0, 2.0, 3.0, 4.0, 5.0, 6.0, -9999.0, 7.0, 8.0, 9.0, 10.0, 11.0], 3, 4).unwrap();
+        let expected = Raster::from_vec(vec![2.0, 2.0, 3.0, 4.0, 5.0, 6.0, -9999.0, 7.0, 8.0, 9.0, 10.0, 10.0], 3, 4).unwrap();
+
+        assert_relative_eq!(raster1.percentile_clip(10.0, 90.0, Some(-9999.0)), expected);
+    }
+
+    #[test]
+    fn test_percentile_clip_interpolated(){
+        let raster1 = Raster::from_vec(vec![0.0, 10.0, 20.0, 30.0, 40.0, 50.0, 60.0, 70.0, 80.0, 90.0, 100.0, 110.0], 3, 4).unwrap();
+        let clipped = raster1.percentile_clip(5.0, 100.0, None);
+
+        assert_relative_eq!(clipped.data[[0, 0]], 5.5, epsilon = 1e-4);
+        assert_eq!(clipped.data[[2, 3]], 110.0);
+    }
+
+    #[test]
+    fn test_to_u8(){
+        let raster = Raster{ nodata: Some(-9999.0), ..Raster::from_vec(vec![0.0, 1.0, 0.5, -9999.0, -1.0, 2.0, 0.25, 0.75, 0.1, 0.9, 0.01, 0.99], 3, 4).unwrap() };
+        let quantized = raster.to_u8(0.0, 1.0, None, 0);
+
+        assert_eq!(quantized.data.into_raw_vec(), vec![0, 255, 128, 0, 0, 255, 64, 191, 26, 230, 3, 252]);
+        assert_eq!(quantized.nodata, None);
+
+        // The endpoints stay at 0 and 255 with a NoData fill.
+        let quantized = raster.to_u8(0.0, 1.0, Some(-9999.0), 0);
+        assert_eq!(quantized.data.into_raw_vec(), vec![0, 255, 128, 0, 0, 255, 64, 191, 26, 230, 3, 252]);
+        assert_eq!(quantized.nodata, Some(0.0));
+
+        let quantized = raster.to_u8(0.0, 1.0, Some(-9999.0), 100);
+        assert_eq!(quantized.data[[0, 3]], 100);
+        assert_eq!(quantized.nodata, Some(100.0));
+    }
+
+    #[test]
+    fn test_u8_round_trip(){
+        let raster = Raster::from_vec(vec![10.0, 12.5, 13.3, 17.0, 19.9, 20.0, 11.1, 14.2, 15.0, 16.6, 18.8, 10.01], 3, 4).unwrap();
+        let restored = raster.to_u8(10.0, 20.0, None, 0).from_u8(10.0, 20.0);
+
+        assert_abs_diff_eq!(restored, raster, epsilon = 0.5 * 10.0 / 255.0);
+        assert_eq!(restored[(0, 0)], 10.0);
+        assert_eq!(restored[(1, 1)], 20.0);
+    }
+
+    #[test]
+    fn test_from_u8_nodata(){
+        let raster = Raster{ nodata: Some(-9999.0), ..Raster::from_vec(vec![-9999.0, 1.0, 0.5, 0.5, 0.5, 0.5, 0.5, 0.5, 0.5, 0.5, 0.5, 0.5], 3, 4).unwrap() };
+        let restored = raster.to_u8(0.0, 1.0, Some(-9999.0), 0).from_u8(0.0, 1.0);
+
+        assert!(restored[(0, 0)].is_nan());
+        assert_eq!(restored[(0, 1)], 1.0);
+        assert!(restored.nodata.unwrap().is_nan());
+    }
+
+    #[test]
+    fn test_u8_round_trip_fill(){
+        let raster = Raster{ nodata: Some(-9999.0), ..Raster::from_vec(vec![10.0, 19.0, -9999.0, 15.0, 10.0, 12.0, 18.0, 11.0, 19.0, 13.0, 14.0, 16.0], 3, 4).unwrap() };
+        let restored = raster.to_u8(10.0, 20.0, Some(-9999.0), 255).from_u8(10.0, 20.0);
+
+        // With a fill above the data, the minimum stays valid and only the
+        // NoData pixel becomes NaN.
+        assert_eq!(restored[(0, 0)], 10.0);
+        assert_eq!(restored[(1, 0)], 10.0);
+        assert_eq!(restored.data.iter().filter(|value| value.is_nan()).count(), 1);
+        assert!(restored[(0, 2)].is_nan());
+        assert_abs_diff_eq!(restored[(0, 3)], 15.0, epsilon = 0.5 * 10.0 / 255.0);
+    }
+}
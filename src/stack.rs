@@ -0,0 +1,261 @@
+use std::path::Path;
+use ndarray::{Array2, Array3, Axis};
+use super::{check_shapes, open_dataset, Mapping, Raster, RasterError, DEFAULT_GEOTRANSFORM};
+
+/// The bands of a dataset, or of single band files, as `(bands, rows, cols)`,
+/// with the NoData value of each band and the georeferencing they share.
+#[derive(Debug, PartialEq)]
+pub struct RasterStack {
+    pub data: Array3<f32>,
+    pub nodata: Vec<Option<f64>>,
+    pub geotransform: [f64; 6],
+    pub projection: String
+}
+
+impl RasterStack {
+    pub fn new<P: AsRef<Path>>(filename: P) -> RasterStack{
+        RasterStack::try_new(filename).unwrap_or_else(|error| panic!("Could not create the raster stack: {}", error))
+    }
+
+    /// Reads the bands `1..=count` of the dataset.
+    pub fn try_new<P: AsRef<Path>>(filename: P) -> Result<RasterStack, RasterError>{
+        let filename = filename.as_ref();
+        let dataset = open_dataset(filename)?;
+        let count = dataset.count();
+        let (cols, rows) = dataset.size();
+
+        let mut data = Array3::zeros((count.max(0) as usize, rows, cols));
+        let mut nodata = Vec::new();
+        for band in 1..=count {
+            let raster = Raster::<f32>::try_new_band(filename, band)?;
+            data.slice_mut(s![band as usize - 1, .., ..]).assign(&raster.data);
+            nodata.push(raster.nodata);
+        }
+
+        Ok(RasterStack{
+            data,
+            nodata,
+            geotransform: dataset.geo_transform().unwrap_or(DEFAULT_GEOTRANSFORM),
+            projection: dataset.projection()
+        })
+    }
+
+    /// Stacks band 1 of each file, in order, checking first that they all have
+    /// the same dimensions. The georeferencing is the one of the first file.
+    pub fn from_files<P: AsRef<Path>>(paths: &[P]) -> Result<RasterStack, RasterError>{
+        check_shapes(paths)?;
+
+        let first = match paths.first() {
+            Some(path) => Raster::<f32>::try_new(path)?,
+            None => return Ok(RasterStack{
+                data: Array3::zeros((0, 0, 0)),
+                nodata: Vec::new(),
+                geotransform: DEFAULT_GEOTRANSFORM,
+                projection: String::new()
+            }),
+        };
+        let (rows, cols) = first.data.dim();
+
+        let mut data = Array3::zeros((paths.len(), rows, cols));
+        let mut nodata = vec![first.nodata];
+        data.slice_mut(s![0, .., ..]).assign(&first.data);
+        for (index, path) in paths.iter().enumerate().skip(1) {
+            let raster = Raster::<f32>::try_new(path)?;
+            data.slice_mut(s![index, .., ..]).assign(&raster.data);
+            nodata.push(raster.nodata);
+        }
+
+        Ok(RasterStack{
+            data,
+            nodata,
+            geotransform: first.geotransform,
+            projection: first.projection
+        })
+    }
+
+    /// Number of bands.
+    pub fn count(&self) -> usize{
+        self.data.dim().0
+    }
+
+    /// Number of bands, the size of the stacking axis, as `count`.
+    pub fn depth(&self) -> usize{
+        self.count()
+    }
+
+    /// Copies the 1-based `band` with its NoData value and the georeferencing.
+    ///
+    /// Panics if the band is out of range.
+    pub fn band(&self, band: usize) -> Raster<f32>{
+        assert!(band >= 1 && band <= self.count(), "band {} is out of range, the stack has {} bands", band, self.count());
+
+        Raster::<f32>{
+            data: self.data.slice(s![band - 1, .., ..]).to_owned(),
+            nodata: self.nodata[band - 1],
+            geotransform: self.geotransform,
+            projection: self.projection.clone()
+        }
+    }
+
+    /// Stretches each band independently to [0, 1] between its `low_pct` and
+    /// `high_pct` percentiles, clamping the values outside, as
+    /// `percentile_clip` followed by `normalize`. The percentiles are computed
+    /// without the pixels equal to the band's NoData value, which are kept as
+    /// they are.
+    pub fn stretch_each(&self, low_pct: f32, high_pct: f32) -> RasterStack{
+        let mut data = self.data.clone();
+        for (index, nodata) in self.nodata.iter().enumerate() {
+            let nodata = nodata.map(|nodata| nodata as f32);
+            let stretched = self.band(index + 1).percentile_clip(low_pct, high_pct, nodata).normalize(nodata);
+            data.slice_mut(s![index, .., ..]).assign(&stretched.data);
+        }
+
+        RasterStack{
+            data,
+            nodata: self.nodata.clone(),
+            geotransform: self.geotransform,
+            projection: self.projection.clone()
+        }
+    }
+
+    /// Sum of the bands multiplied by their weight. Panics if there is not one
+    /// weight per band, see `try_weighted_sum`.
+    pub fn weighted_sum(&self, weights: &[f32]) -> Raster<f32>{
+        self.try_weighted_sum(weights).unwrap_or_else(|error| panic!("Cannot combine the bands: {}", error))
+    }
+
+    /// Sum of the bands multiplied by their weight, failing without one weight
+    /// per band. A pixel equal to its band's NoData value makes the output
+    /// pixel NoData, which is the value of the first band, or NaN without one.
+    pub fn try_weighted_sum(&self, weights: &[f32]) -> Result<Raster<f32>, RasterError>{
+        if weights.len() != self.count() {
+            return Err(RasterError::WeightCount{ bands: self.count(), weights: weights.len() });
+        }
+
+        let fill = self.nodata.first().cloned().unwrap_or(None).map_or(f32::NAN, |nodata| nodata as f32);
+        let (_, rows, cols) = self.data.dim();
+        let mut data = Array2::zeros((rows, cols));
+        let mut missing: Array2<bool> = Array2::default((rows, cols));
+
+        for ((band, &weight), nodata) in self.data.axis_iter(Axis(0)).zip(weights).zip(&self.nodata) {
+            let nodata = nodata.map(|nodata| nodata as f32);
+            data.scaled_add(weight, &band);
+            for (flag, &value) in missing.iter_mut().zip(band.iter()) {
+                *flag = *flag || Some(value) == nodata;
+            }
+        }
+        for (item, &flag) in data.iter_mut().zip(missing.iter()) {
+            if flag {
+                *item = fill;
+            }
+        }
+
+        Ok(Raster::<f32>{
+            data,
+            nodata: Some(f64::from(fill)),
+            geotransform: self.geotransform,
+            projection: self.projection.clone()
+        })
+    }
+}
+
+#[cfg(test)]
+mod test_stack {
+    use super::*;
+
+    #[test]
+    fn test_stack(){
+        let stack = RasterStack::new("data/stack.vrt");
+
+        assert_eq!(stack.count(), 4);
+        assert_eq!(stack.data.dim(), (4, 3, 4));
+        assert_eq!(stack.nodata, vec![Some(-32768.0); 4]);
+        for band in 1..5 {
+            assert_eq!(stack.band(band), Raster::<f32>::new_band("data/stack.vrt", band as isize));
+        }
+    }
+
+    #[test]
+    fn test_stack_single_band(){
+        let stack = RasterStack::new("data/data1.asc");
+
+        assert_eq!(stack.count(), 1);
+        assert_relative_eq!(stack.band(1), Raster::<f32>::new("data/data1.asc"));
+    }
+
+    #[test]
+    fn test_from_files(){
+        let paths: Vec<String> = (1..5).map(|index| format!("data/data{}.asc", index)).collect();
+        let stack = RasterStack::from_files(&paths).unwrap();
+
+        assert_eq!(stack.depth(), 4);
+        assert_eq!(stack.band(1), Raster::<f32>::new("data/data1.asc"));
+        assert_eq!(stack.band(4), Raster::<f32>::new("data/data4.asc"));
+        assert_eq!(stack.data, RasterStack::new("data/stack.vrt").data);
+    }
+
+    #[test]
+    fn test_from_files_mismatch(){
+        assert!(RasterStack::from_files(&["data/data1.asc", "data/data5.asc"]).is_err());
+        assert_eq!(RasterStack::from_files::<&str>(&[]).unwrap().depth(), 0);
+    }
+
+    #[test]
+    fn test_weighted_sum(){
+        let stack = RasterStack::new("data/stack.vrt");
+        let combination = Raster::<f32>::algebra_ordered(vec![("data/data1.asc".to_string(), 0.4), ("data/data2.asc".to_string(), 0.2),
+                                                              ("data/data3.asc".to_string(), 0.2), ("data/data4.asc".to_string(), 0.2)]);
+        let weighted = stack.weighted_sum(&[0.4, 0.2, 0.2, 0.2]);
+
+        assert_relative_eq!(weighted, combination, epsilon = 1e-5f32);
+        assert_relative_eq!(weighted, Raster::<f32>::new("data/result.asc"), epsilon = 1e-5f32);
+        assert_eq!(weighted.nodata, Some(-32768.0));
+    }
+
+    #[test]
+    fn test_weighted_sum_nodata(){
+        let stack = RasterStack{
+            data: Array3::from_shape_vec((2, 1, 3), vec![1.0, -1.0, 3.0, 10.0, 20.0, -2.0]).unwrap(),
+            nodata: vec![Some(-1.0), Some(-2.0)],
+            geotransform: DEFAULT_GEOTRANSFORM,
+            projection: String::new()
+        };
+
+        assert_eq!(stack.weighted_sum(&[1.0, 0.5]).data, array![[6.0, -1.0, -1.0]]);
+    }
+
+    #[test]
+    fn test_weighted_sum_count(){
+        match RasterStack::new("data/stack.vrt").try_weighted_sum(&[0.5, 0.5]) {
+            Err(RasterError::WeightCount{ bands, weights }) => assert_eq!((bands, weights), (4, 2)),
+            other => panic!("Expected a weight count error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_stretch_each(){
+        let stack = RasterStack{
+            data: Array3::from_shape_vec((2, 2, 3), vec![0.0, 1.0, 2.0, 3.0, 4.0, -1.0,
+                                                         100.0, 300.0, 500.0, 700.0, 900.0, 1100.0]).unwrap(),
+            nodata: vec![Some(-1.0), None],
+            geotransform: DEFAULT_GEOTRANSFORM,
+            projection: String::new()
+        };
+        let stretched = stack.stretch_each(0.0, 100.0);
+
+        assert_relative_eq!(stretched.band(1), Raster::<f32>::from(array![[0.0, 0.25, 0.5], [0.75, 1.0, -1.0]]));
+        assert_relative_eq!(stretched.band(2), Raster::<f32>::from(array![[0.0, 0.2, 0.4], [0.6, 0.8, 1.0]]));
+        assert_eq!(stretched.nodata, stack.nodata);
+
+        // Clipping at the quartiles saturates the outer pixels of each band.
+        let clipped = stack.stretch_each(25.0, 75.0);
+        assert_eq!(clipped.band(1).data, array![[0.0, 0.0, 0.5], [1.0, 1.0, -1.0]]);
+        assert_eq!(clipped.band(2).data.iter().filter(|&&value| value == 0.0 || value == 1.0).count(), 4);
+    }
+
+    #[test]
+    #[should_panic(expected = "out of range")]
+    fn test_stack_band_out_of_range(){
+        RasterStack::new("data/stack.vrt").band(5);
+    }
+}
@@ -0,0 +1,542 @@
+use std::collections::{BTreeSet, HashMap};
+use std::fmt;
+use itertools::zip;
+use ordered_float::OrderedFloat;
+use super::{Raster, RasterError};
+
+/// Summary of the valid pixels of a raster. The standard deviation is the
+/// population one. Without valid pixels every value is NaN and `count` is 0.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RasterStats {
+    pub min: f32,
+    pub max: f32,
+    pub mean: f32,
+    pub std: f32,
+    pub count: usize
+}
+
+impl RasterStats {
+    fn from_values(values: &[f32]) -> RasterStats{
+        let count = values.len();
+
+        if count == 0 {
+            return RasterStats{ min: f32::NAN, max: f32::NAN, mean: f32::NAN, std: f32::NAN, count };
+        }
+
+        let min = values.iter().cloned().fold(f32::INFINITY, f32::min);
+        let max = values.iter().cloned().fold(f32::NEG_INFINITY, f32::max);
+        let mean = values.iter().map(|&value| f64::from(value)).sum::<f64>() / count as f64;
+        let variance = values.iter().map(|&value| (f64::from(value) - mean).powi(2)).sum::<f64>() / count as f64;
+
+        RasterStats{ min, max, mean: mean as f32, std: variance.sqrt() as f32, count }
+    }
+}
+
+/// How two rasters differ over the pixels valid in both. `changed` counts the
+/// pixels differing by more than `f32::EPSILON`, `max_abs` is the largest
+/// absolute difference at `max_location`, as `(row, col)`. Without valid
+/// pixels the mean and max are NaN and there is no location.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DiffStats {
+    pub count: usize,
+    pub changed: usize,
+    pub mean_abs: f32,
+    pub max_abs: f32,
+    pub max_location: Option<(usize, usize)>
+}
+
+/// Percentile `pct` in [0, 100] of sorted values, interpolating linearly
+/// between the closest ranks.
+pub(crate) fn percentile(sorted: &[f32], pct: f32) -> f32{
+    let position = f64::from(pct.clamp(0.0, 100.0)) / 100.0 * (sorted.len() - 1) as f64;
+    let (low, high) = (position.floor() as usize, position.ceil() as usize);
+    let fraction = position - low as f64;
+
+    (f64::from(sorted[low]) * (1.0 - fraction) + f64::from(sorted[high]) * fraction) as f32
+}
+
+impl Raster<f32> {
+    /// The raster's NoData value as a pixel value.
+    pub(crate) fn nodata_value(&self) -> Option<f32>{
+        self.nodata.map(|nodata| nodata as f32)
+    }
+
+    /// Pixels not equal to `nodata`, in row-major order.
+    pub(crate) fn valid_values(&self, nodata: Option<f32>) -> Vec<f32>{
+        self.data.iter().cloned().filter(|&value| Some(value) != nodata).collect()
+    }
+
+    /// Pixels not equal to `nodata` with their `(row, col)`, in row-major order.
+    pub fn iter_valid<'a>(&'a self, nodata: Option<f32>) -> impl Iterator<Item = ((usize, usize), f32)> + 'a{
+        self.data.indexed_iter()
+            .map(|(index, &value)| (index, value))
+            .filter(move |&(_, value)| Some(value) != nodata)
+    }
+
+    /// Number of pixels not equal to `nodata`.
+    pub fn valid_count(&self, nodata: f32) -> usize{
+        self.data.iter().filter(|&&value| value != nodata).count()
+    }
+
+    /// Number of pixels equal to `nodata`.
+    pub fn nodata_count(&self, nodata: f32) -> usize{
+        self.data.len() - self.valid_count(nodata)
+    }
+
+    /// Fraction in [0, 1] of the pixels not equal to `nodata`, 0 for an empty
+    /// raster.
+    pub fn coverage(&self, nodata: f32) -> f32{
+        if self.data.is_empty() {
+            return 0.0;
+        }
+        self.valid_count(nodata) as f32 / self.data.len() as f32
+    }
+
+    pub fn stats(&self, nodata: Option<f32>) -> RasterStats{
+        RasterStats::from_values(&self.valid_values(nodata))
+    }
+
+    /// Statistics of the pixels not equal to `nodata`, grouped by the code of
+    /// `zones` at the same position. Zone pixels equal to the zone raster's
+    /// NoData value are left out.
+    ///
+    /// Panics if the shapes differ, see `try_zonal_stats`.
+    pub fn zonal_stats(&self, zones: &Raster<i32>, nodata: Option<f32>) -> HashMap<i32, RasterStats>{
+        self.try_zonal_stats(zones, nodata).unwrap_or_else(|error| panic!("Cannot compute the zonal statistics: {}", error))
+    }
+
+    /// Statistics grouped by zone, failing if the shapes differ.
+    pub fn try_zonal_stats(&self, zones: &Raster<i32>, nodata: Option<f32>) -> Result<HashMap<i32, RasterStats>, RasterError>{
+        self.check_shape(zones)?;
+
+        let zone_nodata = zones.nodata.map(|nodata| nodata as i32);
+        let mut values: HashMap<i32, Vec<f32>> = HashMap::new();
+        for (&value, &zone) in zip(&self.data, &zones.data) {
+            if Some(value) != nodata && Some(zone) != zone_nodata {
+                values.entry(zone).or_default().push(value);
+            }
+        }
+
+        Ok(values.into_iter().map(|(zone, values)| (zone, RasterStats::from_values(&values))).collect())
+    }
+
+    /// Compares with `other` pixel by pixel, leaving out the pixels equal to
+    /// `nodata` in either raster.
+    ///
+    /// Panics if the shapes differ, see `try_difference_stats`.
+    pub fn difference_stats(&self, other: &Raster<f32>, nodata: Option<f32>) -> DiffStats{
+        self.try_difference_stats(other, nodata).unwrap_or_else(|error| panic!("Cannot compare the rasters: {}", error))
+    }
+
+    /// Difference statistics, failing if the shapes differ.
+    pub fn try_difference_stats(&self, other: &Raster<f32>, nodata: Option<f32>) -> Result<DiffStats, RasterError>{
+        self.check_shape(other)?;
+
+        let mut stats = DiffStats{ count: 0, changed: 0, mean_abs: f32::NAN, max_abs: f32::NAN, max_location: None };
+        let mut sum = 0.0;
+        for ((index, &value), &other_value) in zip(self.data.indexed_iter(), &other.data) {
+            if Some(value) == nodata || Some(other_value) == nodata {
+                continue;
+            }
+            let difference = (value - other_value).abs();
+            stats.count += 1;
+            sum += f64::from(difference);
+            if difference > f32::EPSILON {
+                stats.changed += 1;
+            }
+            if stats.max_location.is_none() || difference > stats.max_abs {
+                stats.max_abs = difference;
+                stats.max_location = Some(index);
+            }
+        }
+        if stats.count > 0 {
+            stats.mean_abs = (sum / stats.count as f64) as f32;
+        }
+        Ok(stats)
+    }
+
+    /// Pearson correlation coefficient with `other` over the pixels not equal
+    /// to `nodata` in either raster.
+    ///
+    /// Panics if the shapes differ, see `try_correlation`.
+    pub fn correlation(&self, other: &Raster<f32>, nodata: Option<f32>) -> f32{
+        self.try_correlation(other, nodata).unwrap_or_else(|error| panic!("Cannot correlate the rasters: {}", error))
+    }
+
+    /// Pearson correlation coefficient, failing if the shapes differ. It is
+    /// NaN with less than two common valid pixels, or when either raster is
+    /// constant over them.
+    pub fn try_correlation(&self, other: &Raster<f32>, nodata: Option<f32>) -> Result<f32, RasterError>{
+        self.check_shape(other)?;
+
+        let pairs: Vec<(f64, f64)> = zip(&self.data, &other.data)
+            .filter(|&(&value, &other_value)| Some(value) != nodata && Some(other_value) != nodata)
+            .map(|(&value, &other_value)| (f64::from(value), f64::from(other_value)))
+            .collect();
+        if pairs.len() < 2 {
+            return Ok(f32::NAN);
+        }
+
+        let count = pairs.len() as f64;
+        let mean_x = pairs.iter().map(|pair| pair.0).sum::<f64>() / count;
+        let mean_y = pairs.iter().map(|pair| pair.1).sum::<f64>() / count;
+        let (mut covariance, mut variance_x, mut variance_y) = (0.0, 0.0, 0.0);
+        for &(x, y) in &pairs {
+            covariance += (x - mean_x) * (y - mean_y);
+            variance_x += (x - mean_x).powi(2);
+            variance_y += (y - mean_y).powi(2);
+        }
+        Ok((covariance / (variance_x * variance_y).sqrt()) as f32)
+    }
+
+    /// Counts the valid pixels in `bins` equal-width bins spanning `range`, or
+    /// the pixel min and max without one. Values on the upper edge fall in the
+    /// last bin and values outside the range are not counted.
+    pub fn histogram(&self, bins: usize, range: Option<(f32, f32)>, nodata: Option<f32>) -> Vec<u64>{
+        let mut counts = vec![0; bins];
+        let values = self.valid_values(nodata);
+
+        if bins == 0 || values.is_empty() {
+            return counts;
+        }
+
+        let (low, high) = range.unwrap_or_else(|| {
+            let stats = self.stats(nodata);
+            (stats.min, stats.max)
+        });
+        let width = (f64::from(high) - f64::from(low)) / bins as f64;
+
+        for value in values {
+            if value < low || value > high {
+                continue;
+            }
+            let bin = if width > 0.0 {
+                (((f64::from(value) - f64::from(low)) / width) as usize).min(bins - 1)
+            } else {
+                0
+            };
+            counts[bin] += 1;
+        }
+        counts
+    }
+
+    /// Distinct pixel values not equal to `nodata`, sorted in ascending order,
+    /// such as the codes of a classified raster. A NaN pixel gives a single
+    /// NaN at the end.
+    pub fn unique_values(&self, nodata: Option<f32>) -> Vec<f32>{
+        let values: BTreeSet<OrderedFloat<f32>> = self.valid_values(nodata).into_iter().map(OrderedFloat).collect();
+        values.into_iter().map(|value| value.into_inner()).collect()
+    }
+
+    /// Number of pixels of each distinct value not equal to `nodata`. All
+    /// the NaN pixels are counted under one key.
+    pub fn value_counts(&self, nodata: Option<f32>) -> HashMap<OrderedFloat<f32>, u64>{
+        let mut counts = HashMap::new();
+        for value in self.valid_values(nodata) {
+            *counts.entry(OrderedFloat(value)).or_insert(0) += 1;
+        }
+        counts
+    }
+
+    /// `(row, col)` of the smallest pixel not equal to `nodata`, the first one
+    /// in row-major order on ties, or None without valid pixels. NaN pixels
+    /// are never selected.
+    pub fn argmin(&self, nodata: Option<f32>) -> Option<(usize, usize)>{
+        self.extreme(nodata, |value, best| value < best)
+    }
+
+    /// `(row, col)` of the largest pixel not equal to `nodata`, see `argmin`.
+    pub fn argmax(&self, nodata: Option<f32>) -> Option<(usize, usize)>{
+        self.extreme(nodata, |value, best| value > best)
+    }
+
+    /// First valid pixel for which no later pixel is `better`.
+    fn extreme<F>(&self, nodata: Option<f32>, better: F) -> Option<(usize, usize)>
+        where F: Fn(f32, f32) -> bool
+    {
+        let mut best: Option<((usize, usize), f32)> = None;
+        for (index, value) in self.iter_valid(nodata).filter(|&(_, value)| !value.is_nan()) {
+            if best.is_none_or(|(_, best)| better(value, best)) {
+                best = Some((index, value));
+            }
+        }
+        best.map(|(index, _)| index)
+    }
+
+    /// Smallest pixel, skipping the raster's NoData value.
+    pub fn min(&self) -> f32{
+        self.stats(self.nodata_value()).min
+    }
+
+    /// Largest pixel, skipping the raster's NoData value.
+    pub fn max(&self) -> f32{
+        self.stats(self.nodata_value()).max
+    }
+
+    /// Mean of the pixels, skipping the raster's NoData value.
+    pub fn mean(&self) -> f32{
+        self.stats(self.nodata_value()).mean
+    }
+
+    /// Population standard deviation, skipping the raster's NoData value.
+    pub fn std(&self) -> f32{
+        self.stats(self.nodata_value()).std
+    }
+}
+
+/// One line summary of the shape and of the valid pixels, such as
+/// `Raster 3x4 min=0.39 max=0.81 mean=0.59 nodata=-32768`.
+impl fmt::Display for Raster<f32> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let (rows, cols) = self.data.dim();
+        let stats = self.stats(self.nodata_value());
+
+        write!(f, "Raster {}x{} min={:.2} max={:.2} mean={:.2}", rows, cols, stats.min, stats.max, stats.mean)?;
+        if let Some(nodata) = self.nodata {
+            write!(f, " nodata={}", nodata)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test_stats {
+    use super::*;
+    use ndarray::Array2;
+    use Mapping;
+
+    #[test]
+    fn test_stats(){
+        let raster = Raster::from_vec(vec![2.0, 4.0, 4.0, 4.0, -9999.0, 5.0, 5.0, 7.0, 9.0, -9999.0, -9999.0, -9999.0], 3, 4).unwrap();
+        let stats = raster.stats(Some(-9999.0));
+
+        assert_eq!(stats.count, 8);
+        assert_eq!(stats.min, 2.0);
+        assert_eq!(stats.max, 9.0);
+        assert_relative_eq!(stats.mean, 5.0);
+        assert_relative_eq!(stats.std, 2.0);
+    }
+
+    #[test]
+    fn test_stats_without_nodata(){
+        let raster = Raster::from_vec(vec![2.0, 4.0, 4.0, 4.0, -9999.0, 5.0, 5.0, 7.0, 9.0, -9999.0, -9999.0, -9999.0], 3, 4).unwrap();
+        let stats = raster.stats(None);
+
+        assert_eq!(stats.count, 12);
+        assert_eq!(stats.min, -9999.0);
+    }
+
+    #[test]
+    fn test_stats_all_nodata(){
+        let raster = Raster::from_vec(vec![-9999.0; 12], 3, 4).unwrap();
+        let stats = raster.stats(Some(-9999.0));
+
+        assert_eq!(stats.count, 0);
+        assert!(stats.min.is_nan());
+        assert!(stats.max.is_nan());
+        assert!(stats.mean.is_nan());
+        assert!(stats.std.is_nan());
+    }
+
+    #[test]
+    fn test_counts(){
+        let raster = Raster::from_vec(vec![1.0, -9999.0, 3.0, 4.0, 5.0, -9999.0, 7.0, 8.0, 9.0, 10.0, 11.0, -9999.0], 3, 4).unwrap();
+
+        assert_eq!(raster.valid_count(-9999.0), 9);
+        assert_eq!(raster.nodata_count(-9999.0), 3);
+        assert_relative_eq!(raster.coverage(-9999.0), 0.75);
+        assert_eq!(Raster::<f32>::from(Array2::zeros((0, 0))).coverage(-9999.0), 0.0);
+    }
+
+    #[test]
+    fn test_difference_stats(){
+        let before = Raster::from_vec(vec![1.0, 2.0, 3.0, 4.0, 5.0, 6.0, 7.0, 8.0, 9.0, 10.0, -9999.0, 12.0], 3, 4).unwrap();
+        let mut after = Raster::from_vec(vec![1.0, 2.0, 3.0, 4.0, 5.0, 6.0, 7.0, 8.0, 9.0, 10.0, 0.0, 12.0], 3, 4).unwrap();
+        after.data[[1, 2]] = 4.0;
+        let stats = before.difference_stats(&after, Some(-9999.0));
+
+        assert_eq!(stats.count, 11);
+        assert_eq!(stats.changed, 1);
+        assert_relative_eq!(stats.mean_abs, 3.0 / 11.0);
+        assert_eq!(stats.max_abs, 3.0);
+        assert_eq!(stats.max_location, Some((1, 2)));
+    }
+
+    #[test]
+    fn test_difference_stats_identical(){
+        let raster1 = Raster::<f32>::new("data/data1.asc");
+        let stats = raster1.difference_stats(&Raster::<f32>::new("data/data1.asc"), Some(-32768.0));
+
+        assert_eq!(stats.count, 9);
+        assert_eq!(stats.changed, 0);
+        assert_eq!(stats.max_abs, 0.0);
+        assert_eq!(stats.max_location, Some((0, 0)));
+    }
+
+    #[test]
+    fn test_difference_stats_mismatch(){
+        let raster1 = Raster::from_vec(vec![0.0; 12], 3, 4).unwrap();
+
+        assert!(raster1.try_difference_stats(&Raster::<f32>::from(array![[0.0]]), None).is_err());
+    }
+
+    #[test]
+    fn test_correlation(){
+        let x = Raster::from_vec(vec![1.0, 2.0, 3.0, 4.0, 5.0, 6.0, 7.0, 8.0, 9.0, 10.0, 11.0, -9999.0], 3, 4).unwrap();
+        let linear = x.with_data(x.data.mapv(|value| if value == -9999.0 { 5.0 } else { 2.0 * value + 1.0 }));
+        let inverse = x.with_data(x.data.mapv(|value| -value));
+
+        assert_relative_eq!(x.correlation(&linear, Some(-9999.0)), 1.0);
+        assert_relative_eq!(x.correlation(&inverse, Some(-9999.0)), -1.0);
+    }
+
+    #[test]
+    fn test_correlation_known(){
+        let x = Raster::from_vec(vec![1.0, 2.0, 3.0, 4.0, -9999.0, -9999.0, -9999.0, -9999.0, -9999.0, -9999.0, -9999.0, -9999.0], 3, 4).unwrap();
+        let y = Raster::from_vec(vec![2.0, 1.0, 4.0, 3.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0], 3, 4).unwrap();
+
+        // Covariance 3 over variances 5 and 5.
+        assert_relative_eq!(x.correlation(&y, Some(-9999.0)), 0.6);
+        assert!(x.correlation(&x.with_data(Array2::from_elem((3, 4), 1.0)), Some(-9999.0)).is_nan());
+        assert!(x.try_correlation(&Raster::<f32>::from(array![[0.0]]), None).is_err());
+    }
+
+    #[test]
+    fn test_argmin_argmax(){
+        let raster = Raster::from_vec(vec![3.0, 1.0, 4.0, 1.0, 5.0, 9.0, 2.0, 6.0, -9999.0, 9.0, f32::NAN, 5.0], 3, 4).unwrap();
+
+        assert_eq!(raster.argmax(Some(-9999.0)), Some((1, 1)));
+        assert_eq!(raster.argmin(Some(-9999.0)), Some((0, 1)));
+        assert_eq!(raster.argmin(None), Some((2, 0)));
+    }
+
+    #[test]
+    fn test_argmin_all_nodata(){
+        let raster = Raster::from_vec(vec![-9999.0; 12], 3, 4).unwrap();
+
+        assert_eq!(raster.argmin(Some(-9999.0)), None);
+        assert_eq!(raster.argmax(Some(-9999.0)), None);
+    }
+
+    #[test]
+    fn test_min_max_mean_std(){
+        let raster = Raster::<f32>::new("data/data1.asc");
+
+        assert_relative_eq!(raster.min(), 0.388889);
+        assert_relative_eq!(raster.max(), 0.805556);
+        assert_relative_eq!(raster.mean(), 0.5895066, epsilon = 1e-6);
+        assert_relative_eq!(raster.std(), 0.1187768, epsilon = 1e-6);
+    }
+
+    #[test]
+    fn test_histogram(){
+        let raster = Raster::from_vec((0..12).map(|value| value as f32).collect(), 3, 4).unwrap();
+
+        assert_eq!(raster.histogram(4, None, None), vec![3, 3, 3, 3]);
+        assert_eq!(raster.histogram(3, Some((0.0, 12.0)), None), vec![4, 4, 4]);
+    }
+
+    #[test]
+    fn test_histogram_upper_edge(){
+        let raster = Raster::from_vec((0..12).map(|value| value as f32).collect(), 3, 4).unwrap();
+        let counts = raster.histogram(11, None, None);
+
+        assert_eq!(counts.len(), 11);
+        assert_eq!(counts[10], 2);
+        assert_eq!(counts.iter().sum::<u64>(), 12);
+    }
+
+    #[test]
+    fn test_histogram_nodata_and_range(){
+        let raster = Raster::from_vec(vec![1.0, 1.5, 2.0, 2.5, 3.0, 3.5, -9999.0, -9999.0, 10.0, -1.0, 2.2, 2.8], 3, 4).unwrap();
+
+        assert_eq!(raster.histogram(2, Some((1.0, 3.0)), Some(-9999.0)), vec![2, 5]);
+    }
+
+    #[test]
+    fn test_unique_values(){
+        let raster = Raster::from_vec(vec![3.0, 1.0, 2.0, 2.0, -9999.0, 1.0, 3.0, 3.0, 1.0, -9999.0, 3.0, 2.0], 3, 4).unwrap();
+
+        assert_eq!(raster.unique_values(Some(-9999.0)), vec![1.0, 2.0, 3.0]);
+        assert_eq!(raster.unique_values(None), vec![-9999.0, 1.0, 2.0, 3.0]);
+    }
+
+    #[test]
+    fn test_value_counts(){
+        let raster = Raster::from_vec(vec![3.0, 1.0, 2.0, 2.0, -9999.0, 1.0, 3.0, 3.0, 1.0, -9999.0, 3.0, 2.0], 3, 4).unwrap();
+        let counts = raster.value_counts(Some(-9999.0));
+
+        assert_eq!(counts.len(), 3);
+        assert_eq!(counts[&OrderedFloat(1.0)], 3);
+        assert_eq!(counts[&OrderedFloat(2.0)], 3);
+        assert_eq!(counts[&OrderedFloat(3.0)], 4);
+        assert_eq!(raster.value_counts(None)[&OrderedFloat(-9999.0)], 2);
+    }
+
+    #[test]
+    fn test_unique_values_nan(){
+        let raster = Raster::from_vec(vec![f32::NAN, 1.0, f32::NAN, 1.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0], 3, 4).unwrap();
+        let values = raster.unique_values(None);
+
+        assert_eq!(values.len(), 3);
+        assert!(values[2].is_nan());
+        assert_eq!(raster.value_counts(None)[&OrderedFloat(f32::NAN)], 2);
+    }
+
+    #[test]
+    fn test_display(){
+        let raster1 = Raster::<f32>::new("data/data1.asc");
+
+        assert_eq!(raster1.to_string(), "Raster 3x4 min=0.39 max=0.81 mean=0.59 nodata=-32768");
+    }
+
+    #[test]
+    fn test_display_without_nodata(){
+        let raster1 = Raster::from_vec(vec![1.0, 2.0, 3.0, 4.0, 5.0, 6.0, 7.0, 8.0, 9.0, 10.0, 11.0, 12.0], 3, 4).unwrap();
+
+        assert_eq!(format!("{}", raster1), "Raster 3x4 min=1.00 max=12.00 mean=6.50");
+    }
+
+    #[test]
+    fn test_iter_valid(){
+        let raster1 = Raster::<f32>::from(array![[1.0, 2.0], [-9999.0, 4.0]]);
+        let valid: Vec<((usize, usize), f32)> = raster1.iter_valid(Some(-9999.0)).collect();
+
+        assert_eq!(valid, vec![((0, 0), 1.0), ((0, 1), 2.0), ((1, 1), 4.0)]);
+        assert_eq!(raster1.iter_valid(None).count(), 4);
+    }
+
+    #[test]
+    fn test_zonal_stats(){
+        let values = Raster::<f32>::from(array![[1.0, 2.0, 3.0], [4.0, 5.0, -9999.0], [7.0, 8.0, 9.0]]);
+        let zones = Raster::<i32>::from(array![[1, 1, 2], [1, 1, 2], [2, 2, 2]]);
+        let stats = values.zonal_stats(&zones, Some(-9999.0));
+
+        assert_eq!(stats.len(), 2);
+        assert_relative_eq!(stats[&1].mean, 3.0);
+        assert_eq!(stats[&1].count, 4);
+        assert_relative_eq!(stats[&2].mean, 6.75);
+        assert_eq!(stats[&2].count, 4);
+        assert_eq!(stats[&2].min, 3.0);
+        assert_eq!(stats[&2].max, 9.0);
+    }
+
+    #[test]
+    fn test_zonal_stats_zone_nodata(){
+        let values = Raster::<f32>::from(array![[1.0, 2.0], [3.0, 4.0]]);
+        let mut zones = Raster::<i32>::from(array![[0, 1], [1, 1]]);
+        zones.nodata = Some(0.0);
+
+        let stats = values.zonal_stats(&zones, None);
+        assert!(!stats.contains_key(&0));
+        assert_relative_eq!(stats[&1].mean, 3.0);
+    }
+
+    #[test]
+    fn test_zonal_stats_mismatch(){
+        let values = Raster::<f32>::from(array![[1.0, 2.0], [3.0, 4.0]]);
+        let zones = Raster::<i32>::from(array![[1, 1, 2]]);
+
+        assert!(values.try_zonal_stats(&zones, None).is_err());
+    }
+}
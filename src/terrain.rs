@@ -0,0 +1,167 @@
+use ndarray::Array2;
+use super::Raster;
+
+/// Compass direction in degrees in [0, 360) of the downhill gradient
+/// `(-dzdx, -dzdy)`, with y growing south.
+fn compass_aspect(dzdx: f64, dzdy: f64) -> f64{
+    ((-dzdx).atan2(dzdy).to_degrees() + 360.0) % 360.0
+}
+
+impl Raster<f32> {
+    /// Size of a pixel along x from the geotransform, to use as the cell size
+    /// of the terrain derivatives.
+    pub fn cell_size(&self) -> f64{
+        self.geotransform[1].abs()
+    }
+
+    /// Applies `f` to the Horn gradients `(dz/dx, dz/dy)` of the 3x3 window
+    /// around each pixel, with x growing east and y growing south, in
+    /// elevation units per pixel. Border pixels and windows with a NoData pixel
+    /// become NoData, which is NaN when the raster has no NoData value.
+    fn horn<F>(&self, f: F) -> Raster<f32>
+        where F: Fn(f64, f64) -> Option<f32>
+    {
+        let nodata = self.nodata_value();
+        let fill = nodata.unwrap_or(f32::NAN);
+        let (rows, cols) = self.data.dim();
+
+        let mut data = Array2::from_elem((rows, cols), fill);
+        for row in 1..rows.saturating_sub(1) {
+            for col in 1..cols.saturating_sub(1) {
+                let window = self.data.slice(s![row - 1..row + 2, col - 1..col + 2]);
+                if window.iter().any(|&value| Some(value) == nodata) {
+                    continue;
+                }
+                let z = |r: usize, c: usize| f64::from(window[[r, c]]);
+                let dzdx = ((z(0, 2) + 2.0 * z(1, 2) + z(2, 2)) - (z(0, 0) + 2.0 * z(1, 0) + z(2, 0))) / 8.0;
+                let dzdy = ((z(2, 0) + 2.0 * z(2, 1) + z(2, 2)) - (z(0, 0) + 2.0 * z(0, 1) + z(0, 2))) / 8.0;
+                if let Some(value) = f(dzdx, dzdy) {
+                    data[[row, col]] = value;
+                }
+            }
+        }
+
+        let mut raster = self.with_data(data);
+        raster.nodata = Some(f64::from(fill));
+        raster
+    }
+
+    /// Slope in degrees with Horn's method, for square pixels of `cellsize`
+    /// in elevation units, see `cell_size`. Border pixels are NoData.
+    pub fn slope(&self, cellsize: f64) -> Raster<f32>{
+        self.horn(|dzdx, dzdy| {
+            let gradient = (dzdx.powi(2) + dzdy.powi(2)).sqrt() / cellsize;
+            Some(gradient.atan().to_degrees() as f32)
+        })
+    }
+
+    /// Compass direction the slope faces in degrees, clockwise from north in
+    /// [0, 360), with Horn's method. Border and flat pixels are NoData.
+    pub fn aspect(&self) -> Raster<f32>{
+        self.horn(|dzdx, dzdy| {
+            if dzdx == 0.0 && dzdy == 0.0 {
+                return None;
+            }
+            Some(compass_aspect(dzdx, dzdy) as f32)
+        })
+    }
+
+    /// Shaded relief in [0, 255] lit from the compass `azimuth` and the
+    /// `altitude` above the horizon, both in degrees, for square pixels of
+    /// `cellsize`. Border pixels are NoData.
+    pub fn hillshade(&self, azimuth: f64, altitude: f64, cellsize: f64) -> Raster<f32>{
+        let zenith = (90.0 - altitude).to_radians();
+        let azimuth = azimuth.to_radians();
+
+        self.horn(|dzdx, dzdy| {
+            let slope = ((dzdx.powi(2) + dzdy.powi(2)).sqrt() / cellsize).atan();
+            let aspect = compass_aspect(dzdx, dzdy).to_radians();
+            let shade = zenith.cos() * slope.cos() + zenith.sin() * slope.sin() * (azimuth - aspect).cos();
+            Some((255.0 * shade).max(0.0) as f32)
+        })
+    }
+
+    /// `hillshade` lit by default from the northwest, at an azimuth of 315
+    /// and an altitude of 45 degrees.
+    pub fn hillshade_default(&self, azimuth: Option<f64>, altitude: Option<f64>, cellsize: f64) -> Raster<f32>{
+        self.hillshade(azimuth.unwrap_or(315.0), altitude.unwrap_or(45.0), cellsize)
+    }
+}
+
+#[cfg(test)]
+mod test_terrain {
+    use super::*;
+
+    /// A 4x5 plane where the elevation grows by `dx` per column and `dy` per row.
+    fn plane(dx: f32, dy: f32) -> Raster<f32> {
+        Raster::<f32>::from(Array2::from_shape_fn((4, 5), |(row, col)| dx * col as f32 + dy * row as f32))
+    }
+
+    #[test]
+    fn test_slope(){
+        let slope = plane(2.0, 0.0).slope(2.0);
+
+        assert!(slope.data[[0, 0]].is_nan());
+        assert!(slope.data[[3, 2]].is_nan());
+        for row in 1..3 {
+            for col in 1..4 {
+                assert_relative_eq!(slope.data[[row, col]], 45.0, epsilon = 1e-4);
+            }
+        }
+        assert_relative_eq!(plane(3.0, 4.0).slope(5.0).data[[1, 1]], 45.0, epsilon = 1e-4);
+    }
+
+    #[test]
+    fn test_aspect(){
+        // Rising east faces west, rising south faces north.
+        assert_relative_eq!(plane(1.0, 0.0).aspect().data[[1, 1]], 270.0, epsilon = 1e-4);
+        assert_relative_eq!(plane(-1.0, 0.0).aspect().data[[1, 1]], 90.0, epsilon = 1e-4);
+        assert_relative_eq!(plane(0.0, 1.0).aspect().data[[1, 1]], 0.0, epsilon = 1e-4);
+        assert_relative_eq!(plane(0.0, -1.0).aspect().data[[1, 1]], 180.0, epsilon = 1e-4);
+        assert_relative_eq!(plane(-1.0, -1.0).aspect().data[[1, 1]], 135.0, epsilon = 1e-4);
+    }
+
+    #[test]
+    fn test_aspect_flat_nodata(){
+        let mut raster = plane(0.0, 0.0);
+        raster.nodata = Some(-9999.0);
+        raster.data[[2, 3]] = -9999.0;
+        let aspect = raster.aspect();
+
+        assert!(aspect.data.iter().all(|&value| value == -9999.0));
+        assert_eq!(aspect.nodata, Some(-9999.0));
+    }
+
+    #[test]
+    fn test_hillshade_flat(){
+        let shaded = plane(0.0, 0.0).hillshade(315.0, 45.0, 1.0);
+        let expected = 255.0 * 45f32.to_radians().cos();
+
+        for row in 1..3 {
+            for col in 1..4 {
+                assert_relative_eq!(shaded.data[[row, col]], expected, epsilon = 1e-3);
+            }
+        }
+        assert!(shaded.data[[0, 0]].is_nan());
+    }
+
+    #[test]
+    fn test_hillshade_range(){
+        let surface = Raster::<f32>::from(Array2::from_shape_fn((8, 8), |(row, col)| {
+            10.0 * (row as f32 * 0.7).sin() * (col as f32 * 0.5).cos()
+        }));
+        let shaded = surface.hillshade_default(None, None, 1.0);
+
+        assert!(shaded.data.iter().filter(|value| !value.is_nan()).all(|&value| (0.0..=255.0).contains(&value)));
+        assert!(shaded.nan_equal(&surface.hillshade(315.0, 45.0, 1.0)));
+    }
+
+    #[test]
+    fn test_hillshade_facing(){
+        // A slope facing the light is brighter than one facing away.
+        let towards = plane(1.0, 1.0).hillshade(315.0, 45.0, 1.0);
+        let away = plane(-1.0, -1.0).hillshade(315.0, 45.0, 1.0);
+
+        assert!(towards.data[[1, 1]] > away.data[[1, 1]]);
+    }
+}
@@ -0,0 +1,211 @@
+use gdal::errors::Error as GdalError;
+use gdal::raster::{reproject, Dataset};
+use gdal_sys::GDALResampleAlg;
+use gdal::spatial_ref::SpatialRef;
+use ndarray::Array2;
+use super::{ffi, Raster, RasterError};
+
+/// How `align_to` computes a pixel from the source pixels it covers.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ResampleMethod {
+    /// The source pixel under the target pixel center, for categorical data.
+    Nearest,
+    /// Linear interpolation of the four closest source pixels.
+    Bilinear
+}
+
+impl ResampleMethod {
+    fn gdal_alg(self) -> GDALResampleAlg::Type{
+        match self {
+            ResampleMethod::Nearest => GDALResampleAlg::GRA_NearestNeighbour,
+            ResampleMethod::Bilinear => GDALResampleAlg::GRA_Bilinear,
+        }
+    }
+}
+
+fn warp_error(error: GdalError) -> RasterError{
+    RasterError::Warp(Box::new(error))
+}
+
+/// Reads band 1 of an in-memory dataset.
+fn read_dataset(dataset: &Dataset) -> Result<Array2<f32>, RasterError>{
+    let (cols, rows) = dataset.size();
+    let buffer = dataset.read_full_raster_as::<f32>(1)?;
+    let got = buffer.data.len();
+    Array2::from_shape_vec((rows, cols), buffer.data).map_err(|_| RasterError::Shape{ expected: (rows, cols), got })
+}
+
+impl Raster<f32> {
+    /// Warps the raster into `target_srs`, any definition GDAL accepts such as
+    /// "EPSG:4326", with bilinear resampling. The extent and the grid are the
+    /// ones GDAL suggests, with square pixels of `resolution` target units if
+    /// given. Pixels outside the source are NoData, which is NaN when the
+    /// raster has no NoData value.
+    pub fn reproject(&self, target_srs: &str, resolution: Option<f64>) -> Result<Raster<f32>, RasterError>{
+        let wkt = SpatialRef::from_definition(target_srs).and_then(|srs| srs.to_wkt()).map_err(warp_error)?;
+        let source = self.to_dataset("", "MEM")?;
+
+        let (mut geotransform, mut cols, mut rows) = ffi::suggested_warp_output(&source, &wkt).map_err(warp_error)?;
+        if let Some(resolution) = resolution {
+            cols = (cols as f64 * geotransform[1].abs() / resolution).ceil() as usize;
+            rows = (rows as f64 * geotransform[5].abs() / resolution).ceil() as usize;
+            geotransform[1] = resolution;
+            geotransform[5] = -resolution;
+        }
+
+        let fill = self.nodata.unwrap_or(f64::NAN);
+        let target = Raster::<f32>{
+            data: Array2::from_elem((rows, cols), fill as f32),
+            nodata: Some(fill),
+            geotransform,
+            projection: wkt
+        };
+        let dataset = target.to_dataset("", "MEM")?;
+        reproject(&source, &dataset).map_err(warp_error)?;
+
+        Ok(target.with_data(read_dataset(&dataset)?))
+    }
+
+    /// Resamples the raster onto the grid of `reference`: its geotransform,
+    /// projection and dimensions, so both can be combined pixel by pixel.
+    /// Pixels the raster does not cover are NoData, which is NaN when the
+    /// raster has no NoData value.
+    pub fn align_to(&self, reference: &Raster<f32>, method: ResampleMethod) -> Result<Raster<f32>, RasterError>{
+        let source = self.to_dataset("", "MEM")?;
+
+        let fill = self.nodata.unwrap_or(f64::NAN);
+        let target = Raster::<f32>{
+            data: Array2::from_elem(reference.data.dim(), fill as f32),
+            nodata: Some(fill),
+            geotransform: reference.geotransform,
+            projection: reference.projection.clone()
+        };
+        let dataset = target.to_dataset("", "MEM")?;
+        ffi::reproject_image(&source, &dataset, method.gdal_alg()).map_err(warp_error)?;
+
+        Ok(target.with_data(read_dataset(&dataset)?))
+    }
+
+    /// Resamples the raster to exactly `rows` by `cols` pixels over the same
+    /// extent, scaling the pixel size of the geotransform, see `align_to`.
+    ///
+    /// Panics if the shape is empty or GDAL cannot resample the raster.
+    pub fn resize(&self, rows: usize, cols: usize, method: ResampleMethod) -> Raster<f32>{
+        assert!(rows > 0 && cols > 0, "Cannot resize to an empty shape {:?}", (rows, cols));
+
+        let (old_rows, old_cols) = self.data.dim();
+        let (row_factor, col_factor) = (old_rows as f64 / rows as f64, old_cols as f64 / cols as f64);
+        let mut grid = self.with_data(Array2::<f32>::zeros((rows, cols)));
+        grid.geotransform[1] *= col_factor;
+        grid.geotransform[4] *= col_factor;
+        grid.geotransform[2] *= row_factor;
+        grid.geotransform[5] *= row_factor;
+
+        self.align_to(&grid, method).unwrap_or_else(|error| panic!("Cannot resize the raster: {}", error))
+    }
+}
+
+#[cfg(test)]
+mod test_warp {
+    use super::*;
+    use Mapping;
+
+    #[test]
+    fn test_reproject(){
+        let raster = Raster::<f32>::new("data/data1.asc");
+        let mercator = raster.reproject("EPSG:3857", None).unwrap();
+        let (rows, cols) = mercator.data.dim();
+
+        assert!(mercator.projection().contains("Mercator"));
+        assert_eq!(mercator.nodata, Some(-32768.0));
+        // The origin at 40 degrees west becomes about 4453 km west.
+        assert_relative_eq!(mercator.geo_transform()[0], -4452779.6, epsilon = 1e2);
+        assert!(mercator.geo_transform()[1] > 10000.0);
+        assert!(rows >= 2 && cols >= 3);
+        assert!(mercator.data.iter().any(|&value| value != -32768.0));
+    }
+
+    #[test]
+    fn test_reproject_resolution(){
+        let raster = Raster::<f32>::new("data/data1.asc");
+        let mercator = raster.reproject("EPSG:3857", Some(5000.0)).unwrap();
+
+        assert_eq!(mercator.geo_transform()[1], 5000.0);
+        assert_eq!(mercator.geo_transform()[5], -5000.0);
+        assert!(mercator.data.dim().1 > 4);
+    }
+
+    #[test]
+    fn test_align_to_nearest(){
+        let coarse = Raster{ geotransform: [0.0, 2.0, 0.0, 4.0, 0.0, -2.0], ..Raster::<f32>::from(array![[1.0, 2.0], [3.0, 4.0]]) };
+        let reference = Raster{ geotransform: [0.0, 1.0, 0.0, 4.0, 0.0, -1.0], ..Raster::<f32>::from(Array2::zeros((4, 4))) };
+        let aligned = coarse.align_to(&reference, ResampleMethod::Nearest).unwrap();
+
+        assert_eq!(aligned.data.dim(), (4, 4));
+        assert_eq!(aligned.geo_transform(), reference.geo_transform());
+        assert_eq!(aligned.data, array![[1.0, 1.0, 2.0, 2.0], [1.0, 1.0, 2.0, 2.0], [3.0, 3.0, 4.0, 4.0], [3.0, 3.0, 4.0, 4.0]]);
+        assert!(aligned.checked_add(&reference).is_ok());
+    }
+
+    #[test]
+    fn test_align_to_bilinear(){
+        let coarse = Raster{ geotransform: [0.0, 2.0, 0.0, 4.0, 0.0, -2.0], ..Raster::<f32>::from(array![[1.0, 2.0], [3.0, 4.0]]) };
+        let reference = Raster{ geotransform: [0.0, 1.0, 0.0, 4.0, 0.0, -1.0], ..Raster::<f32>::from(Array2::zeros((4, 4))) };
+        let aligned = coarse.align_to(&reference, ResampleMethod::Bilinear).unwrap();
+
+        assert_eq!(aligned.data.dim(), (4, 4));
+        // A quarter pixel from the center of the top left coarse pixel.
+        assert_relative_eq!(aligned[(1, 1)], 1.75);
+        assert!(aligned.data.iter().all(|&value| value.is_nan() || (1.0..=4.0).contains(&value)));
+    }
+
+    #[test]
+    fn test_align_to_outside(){
+        let coarse = Raster{ nodata: Some(-9999.0), geotransform: [0.0, 1.0, 0.0, 4.0, 0.0, -1.0], ..Raster::<f32>::from(array![[1.0, 2.0], [3.0, 4.0]]) };
+        let reference = Raster{ geotransform: [0.0, 1.0, 0.0, 4.0, 0.0, -1.0], ..Raster::<f32>::from(Array2::zeros((3, 3))) };
+        let aligned = coarse.align_to(&reference, ResampleMethod::Nearest).unwrap();
+
+        assert_eq!(aligned.nodata, Some(-9999.0));
+        assert_eq!(aligned.data, array![[1.0, 2.0, -9999.0], [3.0, 4.0, -9999.0], [-9999.0, -9999.0, -9999.0]]);
+    }
+
+    #[test]
+    fn test_resize_bilinear(){
+        let small = Raster{ geotransform: [0.0, 2.0, 0.0, 4.0, 0.0, -2.0], ..Raster::<f32>::from(array![[1.0, 2.0], [3.0, 4.0]]) };
+        let resized = small.resize(4, 4, ResampleMethod::Bilinear);
+
+        assert_eq!(resized.data.dim(), (4, 4));
+        assert_eq!(resized.geo_transform(), [0.0, 1.0, 0.0, 4.0, 0.0, -1.0]);
+        assert_relative_eq!(resized[(1, 1)], 1.75);
+        assert_relative_eq!(resized[(1, 2)], 2.25);
+        assert_relative_eq!(resized[(2, 1)], 2.75);
+        assert_relative_eq!(resized[(2, 2)], 3.25);
+    }
+
+    #[test]
+    fn test_resize_nearest(){
+        let raster = Raster::<f32>::new("data/data1.asc");
+        let resized = raster.resize(6, 4, ResampleMethod::Nearest);
+
+        assert_eq!(resized.data.dim(), (6, 4));
+        assert_relative_eq!(resized.geo_transform()[1], 0.28);
+        assert_relative_eq!(resized.geo_transform()[5], -0.115);
+        assert_eq!(resized.col(0), array![0.388889, 0.388889, 0.444447, 0.444447, 0.625, 0.625]);
+    }
+
+    #[test]
+    #[should_panic(expected = "Cannot resize to an empty shape")]
+    fn test_resize_empty(){
+        let _ = Raster::<f32>::from(array![[1.0]]).resize(0, 3, ResampleMethod::Nearest);
+    }
+
+    #[test]
+    fn test_reproject_unknown_srs(){
+        let raster = Raster::<f32>::new("data/data1.asc");
+
+        match raster.reproject("NOT:A:CRS", None) {
+            Err(RasterError::Warp(_)) => (),
+            other => panic!("Expected a warp error, got {:?}", other),
+        }
+    }
+}